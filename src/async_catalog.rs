@@ -0,0 +1,172 @@
+//! The async_catalog.rs reads a catalog from a `tokio::io::AsyncRead`, e.g. an exchange set
+//! streamed over the network. Every record is read as a whole from the async reader and then parsed
+//! with the same DDR and Data Record machinery as `Catalog`, so both yield the same records.
+use crate::catalog::{
+    is_padding, parse_ddr, parse_dr, parse_to_usize, CatalogOptions, Record, Result, Warning, DDR,
+    LEADER_LENGTH,
+};
+use crate::error::ErrorKind;
+use failure::ResultExt;
+use futures_util::stream::{self, Stream};
+use std::io::Cursor;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// A catalog read from an async reader, see `Catalog` for the synchronous one.
+///
+/// ```no_run
+/// # use rust_s57::async_catalog::AsyncCatalog;
+/// # async fn run() -> rust_s57::catalog::Result<()> {
+/// let bytes = std::fs::read("CATALOG.031").unwrap();
+/// let mut catalog = AsyncCatalog::new(&bytes[..]).await?;
+/// while let Some(record) = catalog.next_record().await {
+///     println!("{:?}", record?.get_str("CATD", "FILE"));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AsyncCatalog<R: AsyncRead + Unpin> {
+    ddr: DDR,        // Data Descriptive Record
+    rdr: R,          // reader to ask for Data Records
+    record: Vec<u8>, // the bytes of the current Data Record
+    buf: Vec<u8>,    // holds the current Data Record while it is parsed
+    pos: u64,        // bytes read from rdr, counted from the start of the DDR
+    options: CatalogOptions,
+    warnings: Vec<Warning>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncCatalog<R> {
+    pub async fn new(rdr: R) -> Result<AsyncCatalog<R>> {
+        AsyncCatalog::with_options(rdr, &CatalogOptions::default()).await
+    }
+
+    /// Parses the DDR with the given options and returns the catalog, ready to read the Data
+    /// Records.
+    pub async fn with_options(mut rdr: R, options: &CatalogOptions) -> Result<AsyncCatalog<R>> {
+        let mut record = Vec::new();
+        let mut ddr = match read_record(&mut rdr, &mut record).await {
+            Ok(true) => parse_ddr(&mut Cursor::new(&record[..])),
+            Ok(false) => Err(ErrorKind::EOF.into()),
+            Err(err) => Err(err),
+        }
+        .context(ErrorKind::CouldNotParseCatalog)?;
+        options.apply_to_ddr(&mut ddr);
+        let pos = ddr.length() as u64;
+        Ok(AsyncCatalog {
+            ddr,
+            rdr,
+            record,
+            buf: Vec::new(),
+            pos,
+            options: options.clone(),
+            warnings: Vec::new(),
+        })
+    }
+
+    /// Reads and parses the next Data Record, `None` at the end of the reader.
+    pub async fn next_record(&mut self) -> Option<Result<Record>> {
+        match read_record(&mut self.rdr, &mut self.record).await {
+            Ok(true) => (),
+            Ok(false) => {
+                if !self.record.is_empty() {
+                    self.warnings.push(Warning::UnexpectedPadding {
+                        offset: self.pos,
+                        length: self.record.len(),
+                    });
+                    self.pos += self.record.len() as u64;
+                    self.record.clear();
+                }
+                return None;
+            }
+            Err(err) => return Some(Err(err)),
+        }
+        let parsed = parse_dr(
+            &mut Cursor::new(&self.record[..]),
+            &self.ddr,
+            &mut self.buf,
+            self.pos,
+            &self.options,
+            &mut self.warnings,
+        );
+        match parsed {
+            Ok(Some((record, length))) => {
+                self.pos += length as u64;
+                Some(Ok(record))
+            }
+            Ok(None) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+
+    /// The nonfatal deviations found in the records read so far, see `Catalog::warnings`.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Consumes the catalog and streams the remaining Data Records.
+    pub fn records(self) -> impl Stream<Item = Result<Record>> {
+        stream::unfold(self, |mut catalog| async move {
+            let record = catalog.next_record().await?;
+            Some((record, catalog))
+        })
+    }
+}
+
+// Reads one complete record into buf, replacing its contents. Returns false at the end of the
+// reader, also if the reader ends with padding instead of a record, which is then left in buf.
+async fn read_record<R: AsyncRead + Unpin>(rdr: &mut R, buf: &mut Vec<u8>) -> Result<bool> {
+    // The length of the record is stored in its first 5 bytes
+    buf.clear();
+    buf.resize(5, 0);
+    let mut read = 0;
+    while read < 5 {
+        match rdr
+            .read(&mut buf[read..])
+            .await
+            .with_context(|err| ErrorKind::IOError(err.kind()))?
+        {
+            0 => break,
+            n => read += n,
+        }
+    }
+    if read > 0 && is_padding(&buf[..read]) {
+        buf.truncate(read);
+        rdr.read_to_end(buf)
+            .await
+            .with_context(|err| ErrorKind::IOError(err.kind()))?;
+        if !is_padding(buf) {
+            return Err(ErrorKind::InvalidLeader.into());
+        }
+        return Ok(false);
+    }
+    match read {
+        0 => {
+            buf.clear();
+            return Ok(false);
+        }
+        5 => (),
+        got => return Err(ErrorKind::TruncatedRecord { expected: 5, got }.into()),
+    }
+    let length = parse_to_usize(buf)?;
+    if length < LEADER_LENGTH {
+        return Err(ErrorKind::InvalidLeader.into());
+    }
+    buf.resize(length, 0);
+    while read < length {
+        match rdr
+            .read(&mut buf[read..])
+            .await
+            .with_context(|err| ErrorKind::IOError(err.kind()))?
+        {
+            0 => {
+                return Err(ErrorKind::TruncatedRecord {
+                    expected: length,
+                    got: read,
+                }
+                .into())
+            }
+            n => read += n,
+        }
+    }
+    Ok(true)
+}
@@ -3,21 +3,48 @@
 //! [`S-57 Specification`](http://iho.int/iho_pubs/standard/S-57Ed3.1/31Main.pdf). When reading it, remember to also keep
 //! the maintenance document [`S-57 Maintenance`](http://iho.int/iho_pubs/maint/S57md8.pdf) close by since this section
 //! in particular has alot of corrections.
-use crate::data_parser::{Data, ParseData};
+pub use crate::data_parser::Data;
+use crate::data_parser::{ParseData, ParseType};
 use crate::error::{Error, ErrorKind};
+use crate::iso8211;
 use failure::ResultExt;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::convert::TryFrom;
 use std::fmt::{Display, Formatter};
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::iter::FromIterator;
+use std::path::{Path, PathBuf, MAIN_SEPARATOR_STR};
 use std::str::{from_utf8, FromStr};
 
 const DRID: &'static str = "DRID";
 const TOPLVL: &'static str = "0001";
+const FCF: &str = "0000";
+const CATD: &str = "CATD";
+const CATX: &str = "CATX";
+// The name of the catalog in the root folder of an exchange set
+const CATALOG_FILE: &str = "CATALOG.031";
+
+// S-57 stores dates as A(8) strings, these subfields parse as dates
+const DATE_SUBFIELDS: [&str; 2] = ["ISDT", "UADT"];
+
+// The array descriptors of the CATD field in the S-57 catalog profile
+const CATD_DESCRIPTORS: [&str; 12] = [
+    "RCNM", "RCID", "FILE", "LFIL", "VOLM", "IMPL", "SLAT", "WLON", "NLAT", "ELON", "CRCS", "COMT",
+];
+
+/// The reader behind `Catalog::from_path`, a memory mapping of the file with the `mmap` feature
+/// and a buffered file otherwise.
+#[cfg(feature = "mmap")]
+pub type PathReader = Cursor<memmap2::Mmap>;
+#[cfg(not(feature = "mmap"))]
+pub type PathReader = std::io::BufReader<std::fs::File>;
+
+pub(crate) use crate::iso8211::LEADER_LENGTH;
 
 pub(crate) const RECORD_SEPARATOR: u8 = 0x1e;
 pub(crate) const UNIT_SEPARATOR: u8 = 0x1f;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 struct Leader {
     rl: usize,      // Record Length
     il: char,       // Interchange Level
@@ -35,7 +62,7 @@ struct Leader {
     ftf: usize, // Size Of Field Tag Field
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub(crate) struct DirectoryEntry {
     id: String,    // The Id of the field
     length: usize, // The length of the field in bytes
@@ -48,11 +75,38 @@ impl Display for DirectoryEntry {
     }
 }
 
-#[derive(Debug, PartialEq)]
-enum DataStructureCode {
-    SDI, // Single Data Item
-    LS,  // Linear Structure
-    MDS, // Multi-Dimensional structure
+/// The data structure code of the field controls of a field of the DDR.
+///
+/// ```
+/// let code: rust_s57::DataStructureCode = "2".parse().unwrap();
+/// assert_eq!(code, rust_s57::DataStructureCode::MDS);
+/// assert_eq!(code.as_str(), "2");
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DataStructureCode {
+    /// Single Data Item
+    SDI,
+    /// Linear Structure
+    LS,
+    /// Multi-Dimensional structure
+    MDS,
+}
+
+impl DataStructureCode {
+    /// The code as written in the field controls, the inverse of `from_str`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataStructureCode::SDI => "0",
+            DataStructureCode::LS => "1",
+            DataStructureCode::MDS => "2",
+        }
+    }
+}
+
+impl Display for DataStructureCode {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        Display::fmt(self.as_str(), f)
+    }
 }
 
 impl FromStr for DataStructureCode {
@@ -67,14 +121,46 @@ impl FromStr for DataStructureCode {
     }
 }
 
-#[derive(Debug, PartialEq)]
-enum DataTypeCode {
-    CS,  // Character String
-    IP,  // Implicit Point
-    EP,  // Explicit Point (Real)
-    BF,  // Binary Form
-    MDT, // Mixed Data Types
+/// The data type code of the field controls of a field of the DDR.
+///
+/// ```
+/// let code: rust_s57::DataTypeCode = "6".parse().unwrap();
+/// assert_eq!(code, rust_s57::DataTypeCode::MDT);
+/// assert_eq!(code.as_str(), "6");
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DataTypeCode {
+    /// Character String
+    CS,
+    /// Implicit Point
+    IP,
+    /// Explicit Point (Real)
+    EP,
+    /// Binary Form
+    BF,
+    /// Mixed Data Types
+    MDT,
+}
+
+impl DataTypeCode {
+    /// The code as written in the field controls, the inverse of `from_str`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataTypeCode::CS => "0",
+            DataTypeCode::IP => "1",
+            DataTypeCode::EP => "2",
+            DataTypeCode::BF => "5",
+            DataTypeCode::MDT => "6",
+        }
+    }
+}
+
+impl Display for DataTypeCode {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        Display::fmt(self.as_str(), f)
+    }
 }
+
 impl FromStr for DataTypeCode {
     type Err = Error;
     fn from_str(value: &str) -> Result<DataTypeCode> {
@@ -89,13 +175,149 @@ impl FromStr for DataTypeCode {
     }
 }
 
-// Truncated Escape Sequence
-#[derive(Debug, PartialEq)]
-enum TruncEscSeq {
-    LE0, //Lexical Level 0
-    LE1, //Lexical Level 1
-    LE2, //Lexical Level 2
+/// The ISO 8211 interchange level of the file, from the leader of the DDR.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum InterchangeLevel {
+    Level1,
+    Level2,
+    Level3,
+}
+
+impl FromStr for InterchangeLevel {
+    type Err = Error;
+    fn from_str(value: &str) -> Result<InterchangeLevel> {
+        match value {
+            "1" => Ok(InterchangeLevel::Level1),
+            "2" => Ok(InterchangeLevel::Level2),
+            "3" => Ok(InterchangeLevel::Level3),
+            _ => Err(ErrorKind::BadInterchangeLevel(value.to_string()).into()),
+        }
+    }
+}
+
+/// The record name `RCNM` of an S-57 record, binary files store the numeric code and ASCII files
+/// the two letter mnemonic.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RecordName {
+    /// Data Set General Information.
+    DS,
+    /// Data Set Geographic Reference.
+    DP,
+    /// Data Set History.
+    DH,
+    /// Data Set Accuracy.
+    DA,
+    /// Catalogue Directory.
+    CD,
+    /// Catalogue Cross Reference.
+    CR,
+    /// Data Dictionary Definition.
+    ID,
+    /// Data Dictionary Domain.
+    IO,
+    /// Data Dictionary Schema.
+    IS,
+    /// Feature.
+    FE,
+    /// Vector Isolated Node.
+    VI,
+    /// Vector Connected Node.
+    VC,
+    /// Vector Edge.
+    VE,
+    /// Vector Face.
+    VF,
+}
+
+impl FromStr for RecordName {
+    type Err = Error;
+    fn from_str(value: &str) -> Result<RecordName> {
+        match value {
+            "DS" => Ok(RecordName::DS),
+            "DP" => Ok(RecordName::DP),
+            "DH" => Ok(RecordName::DH),
+            "DA" => Ok(RecordName::DA),
+            "CD" => Ok(RecordName::CD),
+            "CR" => Ok(RecordName::CR),
+            "ID" => Ok(RecordName::ID),
+            "IO" => Ok(RecordName::IO),
+            "IS" => Ok(RecordName::IS),
+            "FE" => Ok(RecordName::FE),
+            "VI" => Ok(RecordName::VI),
+            "VC" => Ok(RecordName::VC),
+            "VE" => Ok(RecordName::VE),
+            "VF" => Ok(RecordName::VF),
+            _ => Err(ErrorKind::BadRecordName(value.to_string()).into()),
+        }
+    }
+}
+
+// The catalogue directory records have no numeric code, catalogs are always ASCII
+impl TryFrom<i64> for RecordName {
+    type Error = Error;
+    fn try_from(value: i64) -> Result<RecordName> {
+        match value {
+            10 => Ok(RecordName::DS),
+            20 => Ok(RecordName::DP),
+            30 => Ok(RecordName::DH),
+            40 => Ok(RecordName::DA),
+            60 => Ok(RecordName::CR),
+            70 => Ok(RecordName::ID),
+            80 => Ok(RecordName::IO),
+            90 => Ok(RecordName::IS),
+            100 => Ok(RecordName::FE),
+            110 => Ok(RecordName::VI),
+            120 => Ok(RecordName::VC),
+            130 => Ok(RecordName::VE),
+            140 => Ok(RecordName::VF),
+            _ => Err(ErrorKind::BadRecordName(value.to_string()).into()),
+        }
+    }
+}
+
+/// The implementation of a file referenced by the catalog, from the `IMPL` subfield of `CATD`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FileImplementation {
+    /// ASC, an ISO 8211 file in ASCII implementation.
+    Ascii,
+    /// BIN, an ISO 8211 file in binary implementation.
+    Binary,
+    /// TXT, a plain text file.
+    Text,
+    /// TIF, a picture file.
+    Tiff,
+}
+
+impl FromStr for FileImplementation {
+    type Err = Error;
+    fn from_str(value: &str) -> Result<FileImplementation> {
+        match value {
+            "ASC" => Ok(FileImplementation::Ascii),
+            "BIN" => Ok(FileImplementation::Binary),
+            "TXT" => Ok(FileImplementation::Text),
+            "TIF" => Ok(FileImplementation::Tiff),
+            _ => Err(ErrorKind::BadImplementation(value.to_string()).into()),
+        }
+    }
+}
+
+/// The truncated escape sequence of the field controls of a field of the DDR, which gives the
+/// lexical level of its strings.
+///
+/// ```
+/// let tes: rust_s57::TruncEscSeq = "-A ".parse().unwrap();
+/// assert_eq!(tes, rust_s57::TruncEscSeq::LE1);
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TruncEscSeq {
+    /// Lexical Level 0
+    LE0,
+    /// Lexical Level 1
+    LE1,
+    /// Lexical Level 2
+    LE2,
 }
+
 impl FromStr for TruncEscSeq {
     type Err = Error;
     fn from_str(value: &str) -> Result<TruncEscSeq> {
@@ -108,13 +330,50 @@ impl FromStr for TruncEscSeq {
     }
 }
 
-#[derive(Debug, PartialEq)]
+/// The extended character set from the leader of the DDR. It applies to the fields whose field
+/// controls do not give a character set of their own.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CharacterSet {
+    /// Blanks or " ! ", ASCII as used by S-57.
+    Default,
+    /// "-A ", ISO 8859-1.
+    Latin1,
+    /// "%/A", UCS-2.
+    Ucs2,
+}
+
+impl CharacterSet {
+    /// The lexical level the strings of the fields are decoded with.
+    pub fn lexical_level(&self) -> LexicalLevel {
+        match self {
+            CharacterSet::Default => LexicalLevel::Level0,
+            CharacterSet::Latin1 => LexicalLevel::Level1,
+            CharacterSet::Ucs2 => LexicalLevel::Level2,
+        }
+    }
+}
+
+impl FromStr for CharacterSet {
+    type Err = Error;
+    fn from_str(value: &str) -> Result<CharacterSet> {
+        match value {
+            "   " | " ! " => Ok(CharacterSet::Default),
+            "-A " => Ok(CharacterSet::Latin1),
+            "%/A" => Ok(CharacterSet::Ucs2),
+            _ => Err(ErrorKind::BadCharacterSet(value.to_string()).into()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
 struct FileControlField {
     dsc: DataStructureCode,
     dtc: DataTypeCode,
+    name: String,               // External File Title
+    ftp: Vec<(String, String)>, // Field Tag Pairs (parent, child)
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 struct FieldControls {
     dsc: DataStructureCode,
     dtc: DataTypeCode,
@@ -124,93 +383,247 @@ struct FieldControls {
 }
 
 // Data Descriptive Field Entry
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 struct DDFEntry {
     fic: FieldControls,
     name: String,
     foc: Vec<(String, ParseData)>,
+    repeating: bool, // The subfields repeat until the end of the field
+}
+
+/// A read-only view of the File Control Field (tag `0000`) of the DDR, which declares how the fields
+/// of a record are nested, e.g. that `SG2D` is a child of `VRID`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FileControl<'a>(&'a FileControlField);
+
+impl<'a> FileControl<'a> {
+    /// The external file title, often empty.
+    pub fn name(&self) -> &'a str {
+        &self.0.name
+    }
+
+    /// The field tag pairs as (parent, child), in the order they are declared.
+    pub fn pairs(&self) -> impl Iterator<Item = (&'a str, &'a str)> {
+        self.0
+            .ftp
+            .iter()
+            .map(|(parent, child)| (parent.as_str(), child.as_str()))
+    }
+
+    /// The root of the tree, the first parent which is no child itself. This is the record
+    /// identifier field `0001` for S-57.
+    pub fn root(&self) -> Option<&'a str> {
+        self.pairs()
+            .map(|(parent, _)| parent)
+            .find(|&parent| self.parent(parent).is_none())
+    }
+
+    /// The parent of the field, None for the root and for fields outside the tree.
+    pub fn parent(&self, tag: &str) -> Option<&'a str> {
+        self.pairs()
+            .find(|&(_, child)| child == tag)
+            .map(|(parent, _)| parent)
+    }
+
+    /// The children of the field in the order they are declared.
+    pub fn children<'t>(&self, tag: &'t str) -> impl Iterator<Item = &'a str> + 't
+    where
+        'a: 't,
+    {
+        self.pairs()
+            .filter(move |&(parent, _)| parent == tag)
+            .map(|(_, child)| child)
+    }
+}
+
+/// A read-only view of one Data Descriptive Field declared in the DDR.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldSchema<'a>(&'a DDFEntry);
+
+impl<'a> FieldSchema<'a> {
+    /// The human readable name of the field, e.g. "Catalogue Directory Field".
+    pub fn name(&self) -> &'a str {
+        &self.0.name
+    }
+
+    /// The array descriptors (subfield labels) in the order they appear in a record.
+    pub fn array_descriptors(&self) -> impl Iterator<Item = &'a str> {
+        self.subfields().map(|(label, _)| label)
+    }
+
+    /// The format controls, one per array descriptor. Spares like `X(3)` and the terminator `@`
+    /// are left out.
+    pub fn format_controls(&self) -> impl Iterator<Item = FormatControl<'a>> {
+        self.subfields().map(|(_, fc)| fc)
+    }
+
+    /// Whether the field holds coordinates, like SG2D and SG3D. The heuristic is a repeating field,
+    /// i.e. a multi-dimensional structure or subfields marked with `*`, with both a `YCOO` and an
+    /// `XCOO` array descriptor.
+    pub fn is_coordinate_field(&self) -> bool {
+        self.0.repeating
+            && self.array_descriptors().any(|desc| desc == "YCOO")
+            && self.array_descriptors().any(|desc| desc == "XCOO")
+    }
+
+    /// Pairs of array descriptor and its format control.
+    pub fn subfields(&self) -> impl Iterator<Item = (&'a str, FormatControl<'a>)> {
+        self.0
+            .foc
+            .iter()
+            .filter(|(_, pd)| pd.has_descriptor())
+            .map(|(label, pd)| (label.as_str(), FormatControl(pd)))
+    }
+}
+
+/// The format control of a single subfield. Displays as written in the DDR, e.g. `A(2)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FormatControl<'a>(&'a ParseData);
+
+impl<'a> FormatControl<'a> {
+    /// The width in bytes of a fixed width subfield, or None if it is variable length and
+    /// terminated by a unit separator.
+    pub fn byte_width(&self) -> Option<usize> {
+        self.0.byte_width()
+    }
+
+    /// Appends the data to out in this format, the inverse of parsing the subfield. Variable length
+    /// subfields are terminated by a unit separator. Data of another type, or which does not fit the
+    /// width, is an error.
+    pub fn encode(&self, data: &Data, out: &mut Vec<u8>) -> Result<()> {
+        self.0.encode(data, out)
+    }
+}
+
+impl<'a> Display for FormatControl<'a> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        Display::fmt(self.0, f)
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+// Parses the lengths and offsets of the leader and directory. Some producers pad them with blanks,
+// or even CR/LF, instead of leading zeros, so surrounding ASCII whitespace is ignored.
 pub(crate) fn parse_to_usize(bytes: &[u8]) -> Result<usize> {
     let s = from_utf8(bytes).with_context(|&err| ErrorKind::UtfError(err))?;
+    let s = s.trim_matches(|c: char| c.is_ascii_whitespace());
     Ok(s.parse().with_context(|err: &std::num::ParseIntError| {
         ErrorKind::ParseIntError(err.clone(), s.to_string())
     })?)
 }
 
-pub(crate) fn parse_to_string(bytes: &[u8]) -> Result<String> {
-    Ok(from_utf8(bytes)
-        .with_context(|&err| ErrorKind::UtfError(err))?
-        .to_string())
+// ISO 8859-1 maps every byte directly to the unicode code point with the same value.
+pub(crate) fn decode_latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
 }
 
-fn parse_leader(byte: &[u8], len: usize) -> Result<Leader> {
-    let rl = len;
-    let il = byte[0] as char;
-    let li = byte[1] as char;
-    let cei = byte[2] as char;
-    let vn = byte[3] as char;
-    let ai = byte[4] as char;
-    let fcl = [byte[5] as char, byte[6] as char];
-    let ba = parse_to_usize(&byte[7..12]).context(ErrorKind::InvalidLeader)? as u32;
-    let csi = [byte[12] as char, byte[13] as char, byte[14] as char];
-    let flf = parse_to_usize(&byte[15..16]).context(ErrorKind::InvalidLeader)?;
-    let fpf = parse_to_usize(&byte[16..17]).context(ErrorKind::InvalidLeader)?;
-    let rsv = byte[17] as char;
-    let ftf = parse_to_usize(&byte[18..19]).context(ErrorKind::InvalidLeader)?;
-    Ok(Leader {
-        rl,
-        il,
-        li,
-        cei,
-        vn,
-        ai,
-        fcl,
-        ba,
-        csi,
-        flf,
-        fpf,
-        rsv,
-        ftf,
-    })
+// Decodes the bytes according to the lexical level. Level 0 is plain ASCII, level 1 is
+// ISO 8859-1 and level 2 is UCS-2 (two bytes per character, little-endian as S-57 requires).
+pub(crate) fn parse_to_string(bytes: &[u8], tes: &TruncEscSeq) -> Result<String> {
+    match tes {
+        TruncEscSeq::LE0 => Ok(from_utf8(bytes)
+            .with_context(|&err| ErrorKind::UtfError(err))?
+            .to_string()),
+        TruncEscSeq::LE1 => Ok(decode_latin1(bytes)),
+        TruncEscSeq::LE2 => {
+            let chars = bytes.chunks_exact(2);
+            if !chars.remainder().is_empty() {
+                return Err(ErrorKind::Ucs2Error.into());
+            }
+            chars
+                .map(|c| {
+                    std::char::from_u32(u32::from(u16::from_le_bytes([c[0], c[1]])))
+                        .ok_or_else(|| ErrorKind::Ucs2Error.into())
+                })
+                .collect()
+        }
+    }
 }
 
-// TODO: Change this function to use exact_chunk when it is stable
-fn parse_directory(byte: &[u8], leader: &Leader) -> Result<Vec<DirectoryEntry>> {
-    let chunksize = leader.ftf + leader.flf + leader.fpf;
-    let dir_iter = byte.chunks(chunksize);
-    let mut directories: Vec<DirectoryEntry> = Vec::new();
-    for d in dir_iter {
-        if d.len() != chunksize {
-            return Err(ErrorKind::BadDirectoryData.into());
+impl Leader {
+    // The length of the field controls at the start of every field of the DDR, which is 9 for
+    // S-57. The field controls hold at least the 6 bytes up to the printable graphics.
+    fn field_control_length(&self) -> Result<usize> {
+        match self.fcl.iter().collect::<String>().parse::<usize>() {
+            Ok(fcl) if fcl >= 6 => Ok(fcl),
+            _ => Err(ErrorKind::InvalidLeader.into()),
         }
-        let id = parse_to_string(&d[..leader.ftf])?;
-        let length = parse_to_usize(&d[leader.ftf..leader.ftf + leader.flf])?;
-        let offset = parse_to_usize(&d[leader.ftf + leader.flf..])?;
+    }
+
+    fn character_set(&self) -> Result<CharacterSet> {
+        self.csi.iter().collect::<String>().parse()
+    }
 
-        directories.push(DirectoryEntry { id, length, offset });
+    // The sizes of the parts of the directory entries
+    fn entry_map(&self) -> iso8211::EntryMap {
+        iso8211::EntryMap {
+            length_size: self.flf,
+            position_size: self.fpf,
+            tag_size: self.ftf,
+        }
     }
+}
+
+fn parse_leader(byte: &[u8], len: usize) -> Result<Leader> {
+    let leader = iso8211::parse_leader_entries(byte, len).map_err(|_| ErrorKind::InvalidLeader)?;
+    Ok(Leader {
+        rl: leader.record_length,
+        il: leader.interchange_level as char,
+        li: leader.leader_identifier as char,
+        cei: leader.extension_indicator as char,
+        vn: leader.version as char,
+        ai: leader.application_indicator as char,
+        fcl: leader.field_control_length.map(char::from),
+        ba: u32::try_from(leader.base_address).map_err(|_| ErrorKind::InvalidLeader)?,
+        csi: leader.character_set.map(char::from),
+        flf: leader.entry_map.length_size,
+        fpf: leader.entry_map.position_size,
+        rsv: leader.reserved as char,
+        ftf: leader.entry_map.tag_size,
+    })
+}
 
-    Ok(directories)
+fn parse_directory(byte: &[u8], leader: &Leader) -> Result<Vec<DirectoryEntry>> {
+    let entry_map = leader.entry_map();
+    let count = iso8211::directory_len(byte, &entry_map).map_err(|_| ErrorKind::InvalidLeader)?;
+    let mut entries = vec![iso8211::DirectoryEntry::default(); count];
+    iso8211::parse_directory(byte, &entry_map, &mut entries)
+        .map_err(|_| ErrorKind::BadDirectoryData)?;
+    entries
+        .iter()
+        .map(|entry| {
+            Ok(DirectoryEntry {
+                id: parse_to_string(entry.tag, &TruncEscSeq::LE0)?,
+                length: entry.length,
+                offset: entry.position,
+            })
+        })
+        .collect()
 }
 
 fn parse_field_controls(byte: &[u8]) -> Result<FieldControls> {
-    let dsc = from_utf8(&byte[0..1])
+    let controls = iso8211::parse_field_controls(byte).map_err(|_| ErrorKind::BadFieldControl)?;
+    let dsc = from_utf8(std::slice::from_ref(&controls.data_structure))
         .with_context(|&err| ErrorKind::UtfError(err))?
         .parse::<DataStructureCode>()
         .context(ErrorKind::BadFieldControl)?;
-    let dtc = from_utf8(&byte[1..2])
+    let dtc = from_utf8(std::slice::from_ref(&controls.data_type))
         .with_context(|&err| ErrorKind::UtfError(err))?
         .parse::<DataTypeCode>()
         .context(ErrorKind::BadFieldControl)?;
-    let aux = parse_to_string(&byte[2..4])?;
-    let prt = parse_to_string(&byte[4..6])?;
-    let tes = from_utf8(&byte[6..])
-        .with_context(|&err| ErrorKind::UtfError(err))?
-        .parse::<TruncEscSeq>()
-        .context(ErrorKind::BadFieldControl)?;
+    let aux = parse_to_string(controls.auxiliary, &TruncEscSeq::LE0)?;
+    let prt = parse_to_string(controls.printable_graphics, &TruncEscSeq::LE0)?;
+    // Field controls of 6 bytes have no truncated escape sequence, longer ones than 9 bytes have
+    // further controls which are not interpreted
+    let tes = if controls.escape_sequence.is_empty() {
+        TruncEscSeq::LE0
+    } else {
+        from_utf8(controls.escape_sequence)
+            .with_context(|&err| ErrorKind::UtfError(err))?
+            .parse::<TruncEscSeq>()
+            .context(ErrorKind::BadFieldControl)?
+    };
 
     Ok(FieldControls {
         dsc,
@@ -225,10 +638,10 @@ fn parse_array_descriptors(byte: &[u8]) -> Result<Vec<String>> {
     if byte.is_empty() {
         // The Record Identifier is an unnamed descriptor and therefore the byte
         // array is empty. Since this is a key in a HashMap I use the name DRID
-        // (Data Record ID) to identify this field.
+        // (Data Record ID) to identify this field, see CatalogOptions::record_id_name.
         Ok(vec![String::from(DRID)])
     } else {
-        Ok(parse_to_string(&byte[..])?
+        Ok(parse_to_string(&byte[..], &TruncEscSeq::LE0)?
             .split('!')
             .map(String::from)
             .collect::<Vec<String>>())
@@ -240,196 +653,2103 @@ fn parse_format_controls(byte: &[u8]) -> Result<Vec<ParseData>> {
         Err(ErrorKind::EmptyFormatControls.into())
     } else {
         // Remove surrounding parenthesies and create ParseDatas
-        Ok(parse_to_string(&byte[1..byte.len() - 1])?
-            .split(',')
-            .map(|fc| ParseData::from_str(fc))
-            .collect::<Result<Vec<(usize, ParseData)>>>()?
-            .into_iter()
-            .flat_map(|pd| std::iter::repeat(pd.1).take(pd.0))
-            .collect())
+        expand_format_controls(&parse_to_string(
+            &byte[1..byte.len() - 1],
+            &TruncEscSeq::LE0,
+        )?)
+    }
+}
+
+// Like parse_format_controls, but the format controls may be a single group with a leading *,
+// e.g. (*(b24,b24)), which repeats until the end of the field. Returns the ParseDatas of the group
+// and whether it repeats.
+fn parse_repeating_format_controls(byte: &[u8]) -> Result<(Vec<ParseData>, bool)> {
+    match byte {
+        [b'(', b'*', group @ .., b')'] => Ok((parse_format_controls(group)?, true)),
+        _ => Ok((parse_format_controls(byte)?, false)),
+    }
+}
+
+// Expands a comma separated list of format controls, where a format control may be a
+// parenthesied group with a repeat count, e.g. 2(A(2),I(5)), into ParseDatas
+fn expand_format_controls(s: &str) -> Result<Vec<ParseData>> {
+    let mut pds = Vec::new();
+    for fc in split_format_controls(s)? {
+        let digits = fc.find(|c: char| !c.is_ascii_digit()).unwrap_or(fc.len());
+        if fc[digits..].starts_with('(') {
+            if !fc.ends_with(')') {
+                return Err(ErrorKind::UnParsableFormatControl(fc.to_string()).into());
+            }
+            let count = if digits == 0 {
+                1
+            } else {
+                fc[..digits]
+                    .parse()
+                    .with_context(|err: &std::num::ParseIntError| {
+                        ErrorKind::ParseIntError(err.clone(), fc.to_string())
+                    })?
+            };
+            let group = expand_format_controls(&fc[digits + 1..fc.len() - 1])?;
+            for _ in 0..count {
+                pds.extend(group.iter().cloned());
+            }
+        } else {
+            let (count, pd) = ParseData::from_str(fc)?;
+            pds.extend(std::iter::repeat(pd).take(count));
+        }
+    }
+    Ok(pds)
+}
+
+// Splits on the commas that are not inside parenthesies
+fn split_format_controls(s: &str) -> Result<Vec<&str>> {
+    let mut parts = vec![&[][..]; s.matches(',').count() + 1];
+    let count = iso8211::split_format_controls(s.as_bytes(), &mut parts)
+        .map_err(|_| ErrorKind::UnParsableFormatControl(s.to_string()))?;
+    // The splits are at ASCII characters, so every part is valid UTF-8
+    parts[..count]
+        .iter()
+        .map(|part| Ok(from_utf8(part).with_context(|&err| ErrorKind::UtfError(err))?))
+        .collect()
+}
+
+fn parse_file_control_field(byte: &[u8], ftf: usize, fcl: usize) -> Result<FileControlField> {
+    let parts = byte.split(|&b| b == UNIT_SEPARATOR).collect::<Vec<&[u8]>>();
+    let first = parts.first().ok_or(ErrorKind::InvalidFileControlField)?;
+    if first.len() < fcl || ftf == 0 {
+        return Err(ErrorKind::InvalidFileControlField.into());
+    }
+    let (fic_bytes, name_bytes) = first.split_at(fcl);
+    let fic = parse_field_controls(fic_bytes).context(ErrorKind::InvalidFileControlField)?;
+    let name = parse_to_string(name_bytes, &TruncEscSeq::LE0)?;
+    // The field tag pairs are optional, a file with only one field has none
+    let pairs = parts.get(1).cloned().unwrap_or(&[]);
+    if pairs.len() % (2 * ftf) != 0 {
+        return Err(ErrorKind::InvalidFileControlField.into());
+    }
+    let ftp = pairs
+        .chunks(2 * ftf)
+        .map(|pair| {
+            Ok((
+                parse_to_string(&pair[..ftf], &TruncEscSeq::LE0)?,
+                parse_to_string(&pair[ftf..], &TruncEscSeq::LE0)?,
+            ))
+        })
+        .collect::<Result<Vec<(String, String)>>>()?;
+    Ok(FileControlField {
+        dsc: fic.dsc,
+        dtc: fic.dtc,
+        name,
+        ftp,
+    })
+}
+
+fn parse_fcf(byte: &[u8], dirs: &[DirectoryEntry], fcl: usize) -> Result<FileControlField> {
+    let dir = dirs
+        .iter()
+        .find(|dir| dir.id == FCF)
+        .ok_or(ErrorKind::InvalidFileControlField)?;
+    // The size of the field tags are the same as the tag of the file control field
+    let bytes = field_slice(byte, dir, true, &TruncEscSeq::LE0)?;
+    parse_file_control_field(bytes, dir.id.len(), fcl)
+}
+
+// The bytes of the field described by the directory entry, without the trailing field terminator,
+// which is the record separator, or its UCS-2 code unit 0x1E 0x00 at lexical level 2. Unless
+// strict, a field without field terminator is taken as it is.
+fn field_slice<'a>(
+    byte: &'a [u8],
+    dir: &DirectoryEntry,
+    strict: bool,
+    tes: &TruncEscSeq,
+) -> Result<&'a [u8]> {
+    let terminator: &[u8] = match tes {
+        TruncEscSeq::LE2 => &[RECORD_SEPARATOR, 0],
+        _ => &[RECORD_SEPARATOR],
+    };
+    match dir.offset.checked_add(dir.length) {
+        Some(end) if dir.length > 0 && end <= byte.len() => {
+            let field = &byte[dir.offset..end];
+            match field.strip_suffix(terminator) {
+                Some(field) => Ok(field),
+                None if strict => Err(ErrorKind::MissingFieldTerminator(dir.id.clone()).into()),
+                None => Ok(field),
+            }
+        }
+        _ => Err(ErrorKind::DirectoryOutOfBounds(dir.id.clone()).into()),
     }
 }
 
-fn parse_ddfs(byte: &[u8], dirs: &[DirectoryEntry]) -> Result<HashMap<String, DDFEntry>> {
-    // We should absolutely handle the file control field... later... but for now we skip it.
+fn parse_ddfs(
+    byte: &[u8],
+    dirs: &[DirectoryEntry],
+    fcl: usize,
+) -> Result<HashMap<String, DDFEntry>> {
     dirs.iter()
-        .skip(1)
+        .filter(|dir| dir.id != FCF)
         .map(|dir| {
-            let s = dir.offset;
-            //  take -1 to remove the record separator from the slice
-            let e = dir.offset + dir.length - 1;
-            let ddf_entry = parse_ddf(&byte[s..e]).context(ErrorKind::InvalidDDFS)?;
+            let bytes = field_slice(byte, dir, true, &TruncEscSeq::LE0)?;
+            let ddf_entry = parse_ddf(bytes, fcl).context(ErrorKind::InvalidDDFS)?;
             Ok((dir.id.clone(), ddf_entry))
         })
         .collect()
 }
 
-fn parse_ddf(byte: &[u8]) -> Result<DDFEntry> {
+// fcl is the length of the field controls, see Leader::field_control_length
+fn parse_ddf(byte: &[u8], fcl: usize) -> Result<DDFEntry> {
     let parts = byte.split(|&b| b == UNIT_SEPARATOR).collect::<Vec<&[u8]>>();
-    let (fic_bytes, name_bytes) = parts.get(0).ok_or(ErrorKind::InvalidHeader)?.split_at(9);
-    let name = parse_to_string(name_bytes).context(ErrorKind::CouldNotParseName)?;
+    let first = parts.first().ok_or(ErrorKind::InvalidHeader)?;
+    // The field controls take the first fcl bytes, a shorter segment is truncated
+    if first.len() < fcl {
+        return Err(
+            ErrorKind::InvalidDDF(format!("field controls of {} bytes", first.len())).into(),
+        );
+    }
+    let (fic_bytes, name_bytes) = first.split_at(fcl);
+    let name =
+        parse_to_string(name_bytes, &TruncEscSeq::LE0).context(ErrorKind::CouldNotParseName)?;
     let fic = parse_field_controls(fic_bytes).context(ErrorKind::InvalidDDF(name.clone()))?;
+    let mut array_desc_bytes = *parts.get(1).ok_or(ErrorKind::InvalidDDF(name.clone()))?;
+    let mut repeating = fic.dsc == DataStructureCode::MDS;
+    // A leading * marks a repeating set of subfields, which is also implied by a
+    // multi-dimensional structure
+    if array_desc_bytes.first() == Some(&b'*') {
+        array_desc_bytes = &array_desc_bytes[1..];
+        repeating = true;
+    }
     let array_desc =
-        parse_array_descriptors(parts.get(1).ok_or(ErrorKind::InvalidDDF(name.clone()))?)
-            .context(ErrorKind::InvalidDDF(name.clone()))?;
-    let data_parser =
-        parse_format_controls(parts.get(2).ok_or(ErrorKind::InvalidDDF(name.clone()))?)
+        parse_array_descriptors(array_desc_bytes).context(ErrorKind::InvalidDDF(name.clone()))?;
+    // The repetition may be declared by the format controls instead
+    let (data_parser, repeating_formats) =
+        parse_repeating_format_controls(parts.get(2).ok_or(ErrorKind::InvalidDDF(name.clone()))?)
             .context(ErrorKind::InvalidDDF(name.clone()))?;
-    if array_desc.len() == data_parser.len() {
-        let foc = array_desc
+    let repeating = repeating || repeating_formats;
+    // Spares and terminators have no array descriptor, they are kept with an empty label
+    let formats = data_parser.iter().filter(|pd| pd.has_descriptor()).count();
+    if array_desc.len() == formats {
+        let mut descriptors = array_desc.into_iter();
+        let foc = data_parser
             .into_iter()
-            .zip(data_parser.into_iter())
+            .map(|parser| match parser {
+                ParseData::Spare(_) | ParseData::Terminator => (String::new(), parser),
+                parser => (descriptors.next().unwrap_or_default(), parser),
+            })
+            .map(|(name, parser)| match parser {
+                ParseData::Fixed(ParseType::String, 8)
+                    if DATE_SUBFIELDS.contains(&name.as_str()) =>
+                {
+                    (name, ParseData::Fixed(ParseType::Date, 8))
+                }
+                ParseData::Variable(ParseType::String)
+                    if DATE_SUBFIELDS.contains(&name.as_str()) =>
+                {
+                    (name, ParseData::Variable(ParseType::Date))
+                }
+                parser => (name, parser),
+            })
             .collect();
-        Ok(DDFEntry { fic, name, foc })
+        Ok(DDFEntry {
+            fic,
+            name,
+            foc,
+            repeating,
+        })
     } else {
-        Err(ErrorKind::InvalidDDF(name.clone()).into())
+        Err(ErrorKind::DescriptorFormatMismatch {
+            descriptors: array_desc.len(),
+            formats,
+            field: name,
+        }
+        .into())
     }
 }
 
-#[derive(Debug)]
-struct DDR {
+#[derive(Debug, Clone)]
+pub(crate) struct DDR {
+    leader: Leader,
     dirs: Vec<DirectoryEntry>,
-    // file_control_field,
+    file_control_field: FileControlField,
     data_descriptive_fields: HashMap<String, DDFEntry>,
 }
 
-#[derive(Debug)]
-pub struct Catalog<R: Read> {
-    ddr: DDR, // Data Descriptive Record
-    rdr: R,   // reader to ask for Data Records
+impl DDR {
+    // The length of the DDR, which is the position of the first Data Record
+    pub(crate) fn length(&self) -> usize {
+        self.leader.rl
+    }
+
+    pub(crate) fn stats(&self) -> DdrStats {
+        let ba = self.leader.ba as usize;
+        DdrStats {
+            field_count: self.dirs.len(),
+            field_area_len: self.leader.rl.saturating_sub(ba),
+            directory_len: ba.saturating_sub(LEADER_LENGTH),
+        }
+    }
+
+    pub(crate) fn field_tag_pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.file_control_field
+            .ftp
+            .iter()
+            .map(|(parent, child)| (parent.as_str(), child.as_str()))
+    }
 }
 
-#[derive(Debug)]
-pub struct Record(HashMap<String, Field>);
+/// The shape of a DDR, see `Catalog::ddr_stats`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DdrStats {
+    /// The number of directory entries, i.e. of the fields of the DDR.
+    pub field_count: usize,
+    /// The length of the field area in bytes.
+    pub field_area_len: usize,
+    /// The length of the directory in bytes, including its field terminator.
+    pub directory_len: usize,
+}
 
-pub type Field = HashMap<String, Data>;
+/// The schema of an ISO 8211 file, i.e. its parsed Data Descriptive Record, for inspecting a file
+/// without reading its records, see `parse_ddr_bytes`, or to open files sharing the DDR, see
+/// `Catalog::with_schema`. It keeps the options the DDR was parsed with, to parse the Data Records
+/// with.
+#[derive(Debug, Clone)]
+pub struct CatalogSchema {
+    ddr: DDR,
+    options: CatalogOptions,
+}
 
-impl Record {
-    pub fn id(&self) -> Option<i64> {
-        self.0.get(TOPLVL).and_then(|m| m.get(DRID)).and_then(|v| {
-            if let Data::Integer(i) = v {
-                *i
-            } else {
-                None
-            }
-        })
+impl CatalogSchema {
+    /// The length of the DDR, which is the position of the first Data Record.
+    pub fn record_length(&self) -> usize {
+        self.ddr.leader.rl
     }
 
-    pub fn get(&self, arr_desc: &str) -> Option<&Field> {
-        self.0.get(arr_desc)
+    /// The interchange level from the leader of the DDR.
+    pub fn interchange_level(&self) -> Result<InterchangeLevel> {
+        self.ddr.leader.il.to_string().parse()
     }
-}
 
-impl<R: Read> Catalog<R> {
-    pub fn new(mut rdr: R) -> Result<Catalog<R>> {
-        let ddr = parse_ddr(&mut rdr).context(ErrorKind::CouldNotParseCatalog)?;
-        Ok(Catalog { ddr, rdr })
+    /// The application indicator from the leader of the DDR, a blank for S-57.
+    pub fn application_indicator(&self) -> char {
+        self.ddr.leader.ai
     }
 
-    fn parse_dr(&mut self) -> Result<Option<Record>> {
-        let (dirs, field_data) = match parse_dir_and_field_area(&mut self.rdr) {
-            Ok(ok) => ok,
-            Err(err) => match err.kind() {
-                ErrorKind::EOF => return Ok(None),
-                _ => return Err(err),
-            },
-        };
-        let mut cur = std::io::Cursor::new(field_data);
-        let mut record = Record(HashMap::new());
-        for dir_entry in dirs.iter() {
-            let ddf_entry = self
-                .ddr
-                .data_descriptive_fields
-                .get(&dir_entry.id)
-                .ok_or(ErrorKind::InvalidDR)?;
-            let field_area = ddf_entry
-                .foc
-                .iter()
-                .map(|(name, parser)| Ok((name.clone(), parser.parse(&mut cur)?)))
-                .collect::<Result<Field>>()
-                .context(ErrorKind::InvalidDR)?;
-            // "Jump over" the last RECORD_SEPARATOR byte
-            cur.seek(SeekFrom::Current(1))
-                .with_context(|err| ErrorKind::IOError(err.kind()))?;
-            record.0.insert(dir_entry.id.clone(), field_area);
-        }
-        Ok(Some(record))
+    /// The position of the field area, counted from the start of the DDR.
+    pub fn base_address(&self) -> u32 {
+        self.ddr.leader.ba
+    }
+
+    /// The extended character set indicator from the leader of the DDR.
+    pub fn character_set(&self) -> [char; 3] {
+        self.ddr.leader.csi
+    }
+
+    /// The extended character set indicator from the leader of the DDR, interpreted.
+    pub fn extended_character_set(&self) -> Result<CharacterSet> {
+        self.ddr.leader.character_set()
+    }
+
+    /// The directory of the DDR as (field tag, length, position) in the order of the directory,
+    /// the position counted from the start of the field area.
+    pub fn directory(&self) -> impl Iterator<Item = (&str, usize, usize)> {
+        self.ddr
+            .dirs
+            .iter()
+            .map(|dir| (dir.id.as_str(), dir.length, dir.offset))
+    }
+
+    /// Iterates over the fields declared in the DDR as (field tag, schema) pairs.
+    pub fn fields(&self) -> impl Iterator<Item = (&str, FieldSchema<'_>)> {
+        self.ddr
+            .data_descriptive_fields
+            .iter()
+            .map(|(tag, entry)| (tag.as_str(), FieldSchema(entry)))
+    }
+
+    /// The schema of the field with the given tag, e.g. `CATD`.
+    pub fn field(&self, tag: &str) -> Option<FieldSchema<'_>> {
+        self.ddr.data_descriptive_fields.get(tag).map(FieldSchema)
+    }
+
+    /// The field tag pairs from the File Control Field as (parent, child).
+    pub fn field_tag_pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.ddr.field_tag_pairs()
+    }
+
+    /// The File Control Field of the DDR, the tree of the fields in a record.
+    pub fn file_control(&self) -> FileControl<'_> {
+        FileControl(&self.ddr.file_control_field)
     }
 }
 
-impl<R: Read> Iterator for Catalog<R> {
-    type Item = Result<Record>;
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.parse_dr() {
-            Ok(Some(dr)) => Some(Ok(dr)),
-            Ok(None) => None,
-            Err(err) => Some(Err(err)),
+/// Parses the DDR at the start of the bytes, anything after it is ignored. The schema has the
+/// default options.
+pub fn parse_ddr_bytes(bytes: &[u8]) -> Result<CatalogSchema> {
+    let ddr = parse_ddr(&mut Cursor::new(bytes)).context(ErrorKind::InvalidDDR)?;
+    Ok(CatalogSchema {
+        ddr,
+        options: CatalogOptions::default(),
+    })
+}
+
+#[derive(Debug)]
+pub struct Catalog<R: Read> {
+    schema: CatalogSchema, // Data Descriptive Record and the options to parse the records with
+    rdr: R,                // reader to ask for Data Records
+    pos: u64,              // bytes read from rdr, counted from the start of the DDR
+    first: u64,            // position of the first Data Record, the length of the DDR of rdr
+    index: HashMap<i64, u64>, // record id to the position of the record
+    start: Option<u64>,    // absolute position of the first Data Record, if known
+    buf: Vec<u8>, // holds the current Data Record, reused to avoid an allocation per record
+    warnings: Vec<Warning>,
+    parsed: (usize, u64), // number and total length of the Data Records parsed so far
+    peeked: Option<Record>, // the next record, parsed by peek, the reader is still before it
+    pending: Vec<u8>,     // bytes read ahead by peek_buffered, read again before rdr
+}
+
+/// A nonfatal deviation from the standard, found while parsing a catalog, see
+/// `Catalog::warnings`.
+#[derive(Debug, PartialEq, Clone)]
+#[non_exhaustive]
+pub enum Warning {
+    /// The file ends with blanks or NULs of the given length instead of a record.
+    UnexpectedPadding { offset: u64, length: usize },
+    /// The record identifier field `0001` is not the first field of the record, `tag` is.
+    NonCanonicalFieldOrder { offset: u64, tag: String },
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Warning::UnexpectedPadding { offset, length } => {
+                write!(f, "{} bytes of padding at offset {}", length, offset)
+            }
+            Warning::NonCanonicalFieldOrder { offset, tag } => write!(
+                f,
+                "The record at offset {} starts with field '{}' instead of '{}'",
+                offset, tag, TOPLVL
+            ),
         }
     }
 }
-fn parse_dir_and_field_area<R: Read>(rdr: &mut R) -> Result<(Vec<DirectoryEntry>, Vec<u8>)> {
-    // Read the length of the DDR, stored in the first 5 bytes
-    let mut len_bytes = [0; 5];
-    let nr_of_bytes = rdr
-        .read(&mut len_bytes)
-        .with_context(|err| ErrorKind::IOError(err.kind()))?;
-    match nr_of_bytes {
-        0 => return Err(ErrorKind::EOF.into()),
-        5 => (),
-        _ => return Err(ErrorKind::IOError(std::io::ErrorKind::UnexpectedEof).into()),
-    }
 
-    // Read the rest of the DDR
-    let length = parse_to_usize(&len_bytes)?;
-    let mut data = vec![0; length - 5];
-    rdr.read_exact(&mut data)
-        .with_context(|err| ErrorKind::IOError(err.kind()))?;
-    let leader = parse_leader(&data[..19], length)?;
-    let field_area_idx = match data.iter().position(|&b| b == RECORD_SEPARATOR) {
-        Some(index) => index,
-        None => return Err(ErrorKind::BadDirectoryData.into()),
-    };
-    let dirs = parse_directory(&data[19..field_area_idx], &leader)?;
-    Ok((dirs, data[field_area_idx + 1..].to_vec()))
+/// The identity of a record, parsed from its record identifier field `0001` and the `RCNM` and
+/// `RCID` subfields of the field following it, e.g. `CATD`, `DSID`, `FRID` or `VRID`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RecordHeader {
+    /// The ISO 8211 record identifier.
+    pub id: i64,
+    /// The record name `RCNM`, None for records without it.
+    pub record_name: Option<RecordName>,
+    /// The record id `RCID`, unique per record name.
+    pub record_id: Option<i64>,
 }
 
-fn parse_ddr<R: Read>(rdr: &mut R) -> Result<DDR> {
-    let (dirs, field_area) = parse_dir_and_field_area(rdr)?;
-    let data_descriptive_fields = parse_ddfs(&field_area, &dirs).context(ErrorKind::InvalidDDR)?;
+/// A Data Record. Records are equal when they hold the same fields in the same directory order,
+/// regardless of where they were read from, see `Data` for the equality of subfields.
+#[derive(Debug, Default, Clone)]
+pub struct Record {
+    fields: HashMap<String, Field>,
+    tags: Vec<String>, // The field tags in directory order
+    offset: u64,       // The position of the record, counted from the start of the DDR
+    length: usize,     // The record length from the leader
+    raw: Vec<u8>,      // The field area as read, see CatalogOptions::retain_raw_fields
+    // The bytes of the fields in raw, without the field terminator
+    spans: HashMap<String, (usize, usize)>,
+}
 
-    Ok(DDR {
-        dirs,
-        data_descriptive_fields,
-    })
+pub type Field = HashMap<String, Data>;
+
+impl PartialEq for Record {
+    fn eq(&self, other: &Record) -> bool {
+        self.tags == other.tags && self.fields == other.fields
+    }
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::data_parser::ParseType;
+impl Eq for Record {}
 
-    fn get_test_leader() -> Leader {
-        Leader {
-            rl: 241,
-            il: '3',
-            li: 'L',
-            cei: 'E',
-            vn: '1',
-            ai: ' ',
-            fcl: ['0', '9'],
-            ba: 58,
-            csi: [' ', '!', ' '],
-            flf: 3,
-            fpf: 4,
-            rsv: '0',
-            ftf: 4,
+// Writes the subfields sorted by name, so the dump is stable
+fn fmt_subfields(f: &mut Formatter, field: &Field, indent: &str) -> std::fmt::Result {
+    let mut subfields = field.iter().collect::<Vec<(&String, &Data)>>();
+    subfields.sort_by_key(|(name, _)| *name);
+    for (name, data) in subfields {
+        writeln!(f, "{}{}: {}", indent, name, data)?;
+    }
+    Ok(())
+}
+
+/// Dumps the fields in directory order, one line per subfield. The repetitions of a repeating
+/// field are numbered, e.g.
+///
+/// ```text
+/// VRID
+///   RCID: 1
+///   RCNM: 110
+/// SG2D
+///   [0]
+///     XCOO: -102500000
+///     YCOO: 593456789
+/// ```
+impl Display for Record {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        for (tag, field) in self.iter_fields() {
+            writeln!(f, "{}", tag)?;
+            let elements = field.values().find_map(|data| match data {
+                Data::Array(elements) => Some(elements),
+                _ => None,
+            });
+            match elements {
+                Some(elements) => {
+                    for (i, element) in elements.iter().enumerate() {
+                        writeln!(f, "  [{}]", i)?;
+                        fmt_subfields(f, element, "    ")?;
+                    }
+                }
+                None => fmt_subfields(f, field, "  ")?,
+            }
         }
+        Ok(())
     }
+}
 
-    fn get_test_directory() -> Vec<DirectoryEntry> {
-        vec![
-            DirectoryEntry {
-                id: "0000".to_string(),
+impl Record {
+    pub fn id(&self) -> Option<i64> {
+        self.header().map(|header| header.id)
+    }
+
+    /// The header of the record, or None if it has no record identifier field `0001`.
+    pub fn header(&self) -> Option<RecordHeader> {
+        Some(RecordHeader {
+            id: self.record_id()?,
+            record_name: self.record_name(),
+            record_id: self
+                .iter_fields()
+                .find_map(|(_, field)| field.get("RCID"))
+                .and_then(Data::as_i64),
+        })
+    }
+
+    // The unnamed subfield of the record identifier field, which is not DRID if it has been
+    // renamed, see CatalogOptions::record_id_name
+    fn record_id(&self) -> Option<i64> {
+        let field = self.get(TOPLVL)?;
+        let id = match field.get(DRID) {
+            Some(id) => id,
+            None if field.len() == 1 => field.values().next()?,
+            None => return None,
+        };
+        id.as_i64()
+    }
+
+    pub fn get(&self, arr_desc: &str) -> Option<&Field> {
+        self.fields.get(arr_desc)
+    }
+
+    /// Returns the repetitions of a repeating field, e.g. the coordinates of SG2D, or None if the
+    /// field is missing or does not repeat.
+    pub fn get_array(&self, field: &str) -> Option<&[Field]> {
+        self.get(field)?.values().find_map(|data| match data {
+            Data::Array(elements) => Some(elements.as_slice()),
+            _ => None,
+        })
+    }
+
+    /// The position of the record counted from the start of the DDR, which is the start of the file
+    /// for a catalog read from the beginning, together with the record length from its leader.
+    pub fn source_span(&self) -> (u64, usize) {
+        (self.offset, self.length)
+    }
+
+    /// A reader over the bytes of the field as read, without the field terminator, e.g. to pass a
+    /// binary field to another decoder. None if the field is missing, was changed since, e.g. by an
+    /// update, or the record was not read with `CatalogOptions::retain_raw_fields`.
+    pub fn field_reader(&self, tag: &str) -> Option<impl Read + '_> {
+        let &(start, end) = self.spans.get(tag)?;
+        Some(&self.raw[start..end])
+    }
+
+    /// The tags of the fields of the record, in directory order.
+    pub fn field_tags(&self) -> impl Iterator<Item = &str> {
+        self.tags.iter().map(String::as_str)
+    }
+
+    /// Iterates over the fields in the order they appear in the directory of the record.
+    pub fn iter_fields(&self) -> impl Iterator<Item = (&str, &Field)> {
+        self.tags
+            .iter()
+            .map(move |tag| (tag.as_str(), &self.fields[tag]))
+    }
+
+    /// Flattens the record to a map from `FIELD.SUBFIELD` keys, e.g. `CATD.FILE`, to the data. The
+    /// subfields of a repeating field are indexed by repetition, e.g. `SG2D.YCOO[0]`.
+    pub fn to_map(&self) -> BTreeMap<String, Data> {
+        let mut map = BTreeMap::new();
+        for (tag, field) in self.iter_fields() {
+            for (name, data) in field {
+                match data {
+                    Data::Array(elements) => {
+                        for (i, element) in elements.iter().enumerate() {
+                            for (subfield, data) in element {
+                                map.insert(format!("{}.{}[{}]", tag, subfield, i), data.clone());
+                            }
+                        }
+                    }
+                    _ => {
+                        map.insert(format!("{}.{}", tag, name), data.clone());
+                    }
+                }
+            }
+        }
+        map
+    }
+
+    pub(crate) fn get_mut(&mut self, arr_desc: &str) -> Option<&mut Field> {
+        self.spans.remove(arr_desc);
+        self.fields.get_mut(arr_desc)
+    }
+
+    pub(crate) fn insert(&mut self, tag: String, field: Field) {
+        self.spans.remove(&tag);
+        if self.fields.insert(tag.clone(), field).is_none() {
+            self.tags.push(tag);
+        }
+    }
+
+    /// Returns the subfield as a string, or None if it is missing or not a string. An empty subfield
+    /// is an empty string.
+    pub fn get_str(&self, field: &str, subfield: &str) -> Option<&str> {
+        self.get(field)?.get(subfield)?.as_str()
+    }
+
+    /// Returns the subfield as an integer, or None if it is missing, empty or not an integer. So an
+    /// empty record identifier gives a `Record::id` of None.
+    pub fn get_i64(&self, field: &str, subfield: &str) -> Option<i64> {
+        self.get(field)?.get(subfield)?.as_i64()
+    }
+
+    /// The record name from the `RCNM` subfield of the first field holding one, or None if there is
+    /// none or the code is unknown.
+    pub fn record_name(&self) -> Option<RecordName> {
+        self.iter_fields()
+            .find_map(|(_, field)| field.get("RCNM"))
+            .and_then(|rcnm| match rcnm {
+                Data::Integer(Some(code)) => RecordName::try_from(*code).ok(),
+                Data::String(code) => code.parse().ok(),
+                _ => None,
+            })
+    }
+
+    /// The kind of the record, from the first field in directory order that identifies one.
+    pub fn kind(&self) -> RecordKind {
+        self.field_tags()
+            .find_map(|tag| match tag {
+                "DSID" => Some(RecordKind::Dataset),
+                "FRID" => Some(RecordKind::Feature),
+                "VRID" => Some(RecordKind::Vector),
+                CATD => Some(RecordKind::CatalogEntry),
+                CATX => Some(RecordKind::CatalogCrossReference),
+                _ => None,
+            })
+            .unwrap_or(RecordKind::Unknown)
+    }
+
+    /// The comment of a catalog record from the `CATD/COMT` subfield, or None if there is no
+    /// comment. An empty subfield is no comment either, unlike for `Record::get_str`.
+    pub fn comment(&self) -> Option<&str> {
+        self.get_str(CATD, "COMT").filter(|comt| !comt.is_empty())
+    }
+
+    /// The label of the volume holding the file referenced by a catalog record, e.g. `V01X01` for
+    /// the first of several volumes, from the `CATD/VOLM` subfield. None if it is missing or empty.
+    pub fn volume(&self) -> Option<&str> {
+        self.get_str(CATD, "VOLM").filter(|volm| !volm.is_empty())
+    }
+
+    /// The implementation of the file referenced by a catalog record, or None if the record has no
+    /// `IMPL` subfield or its value is unknown.
+    pub fn implementation(&self) -> Option<FileImplementation> {
+        self.get_str(CATD, "IMPL")?.parse().ok()
+    }
+
+    /// The Coordinate Multiplication Factor from the DSPM field of the data set parameter record.
+    /// None if the subfield is missing, a BadAttribute error if it does not fit a u32.
+    pub fn comf(&self) -> Result<Option<u32>> {
+        self.multiplication_factor("COMF")
+    }
+
+    /// The Sounding Multiplication Factor from the DSPM field of the data set parameter record.
+    /// None if the subfield is missing, a BadAttribute error if it does not fit a u32.
+    pub fn somf(&self) -> Result<Option<u32>> {
+        self.multiplication_factor("SOMF")
+    }
+
+    fn multiplication_factor(&self, subfield: &str) -> Result<Option<u32>> {
+        match self.get_i64("DSPM", subfield) {
+            Some(value) => Ok(Some(to_u32(subfield, value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The first 2D coordinate of a vector record (SG2D field) as (latitude, longitude) in degrees,
+    /// scaled by the COMF of the data set, see `Record::comf`.
+    pub fn coordinate(&self, comf: u32) -> Option<(f64, f64)> {
+        let sg2d = self.get_array("SG2D")?.first()?;
+        match (sg2d.get("YCOO")?, sg2d.get("XCOO")?) {
+            (Data::Integer(Some(y)), Data::Integer(Some(x))) => {
+                Some((apply_comf(*y, comf), apply_comf(*x, comf)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the subfield as a float, or None if it is missing, empty or not a float.
+    pub fn get_f64(&self, field: &str, subfield: &str) -> Option<f64> {
+        self.get(field)?.get(subfield)?.as_f64()
+    }
+}
+
+// Serializes as an object keyed by field tag in directory order, holding objects keyed by
+// subfield
+#[cfg(feature = "serde")]
+impl serde::Serialize for Record {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map(Some(self.tags.len()))?;
+        for (tag, field) in self.iter_fields() {
+            map.serialize_entry(tag, field)?;
+        }
+        map.end()
+    }
+}
+
+/// The kind of a record, told by the field identifying it, see `Record::kind`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[non_exhaustive]
+pub enum RecordKind {
+    /// `DSID`, the data set general information of a cell.
+    Dataset,
+    /// `FRID`, a feature.
+    Feature,
+    /// `VRID`, a vector.
+    Vector,
+    /// `CATD`, a catalogue directory entry.
+    CatalogEntry,
+    /// `CATX`, a catalogue cross reference.
+    CatalogCrossReference,
+    /// None of the above.
+    Unknown,
+}
+
+/// Records indexed by their record identifier, see `Record::id`, for random access after reading
+/// a file, e.g. `catalog.collect::<Result<Dataset>>()`. Records without an identifier are kept
+/// apart, see `Dataset::unidentified`, and a record replaces an earlier one with the same
+/// identifier.
+#[derive(Debug, Default, Clone)]
+pub struct Dataset {
+    records: HashMap<i64, Record>,
+    ids: Vec<i64>, // The identifiers in the order the records were collected
+    unidentified: Vec<Record>,
+}
+
+impl Dataset {
+    /// The record with the given identifier.
+    pub fn get(&self, id: i64) -> Option<&Record> {
+        self.records.get(&id)
+    }
+
+    /// The number of records with an identifier.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Iterates over the records with an identifier in the order they were collected.
+    pub fn iter(&self) -> impl Iterator<Item = &Record> {
+        self.ids.iter().map(move |id| &self.records[id])
+    }
+
+    /// The records without an identifier, i.e. without the `0001` field.
+    pub fn unidentified(&self) -> &[Record] {
+        &self.unidentified
+    }
+}
+
+impl Extend<Record> for Dataset {
+    fn extend<I: IntoIterator<Item = Record>>(&mut self, records: I) {
+        for record in records {
+            match record.id() {
+                Some(id) => {
+                    if self.records.insert(id, record).is_none() {
+                        self.ids.push(id);
+                    }
+                }
+                None => self.unidentified.push(record),
+            }
+        }
+    }
+}
+
+impl FromIterator<Record> for Dataset {
+    fn from_iter<I: IntoIterator<Item = Record>>(records: I) -> Dataset {
+        let mut dataset = Dataset::default();
+        dataset.extend(records);
+        dataset
+    }
+}
+
+/// The path of the file referenced by the `FILE` subfield of a catalog record. `FILE` is relative to
+/// the catalog and uses `\` as separator, which is replaced by the separator of the platform
+/// before it is joined to `catalog_dir`. An absolute `FILE` replaces `catalog_dir`.
+pub fn resolve_file(record: &Record, catalog_dir: &Path) -> Option<PathBuf> {
+    let file = record.get_str(CATD, "FILE")?;
+    if file.is_empty() {
+        return None;
+    }
+    let file = file.replace(['\\', '/'], MAIN_SEPARATOR_STR);
+    Some(catalog_dir.join(file))
+}
+
+/// A file referenced by the catalog of an exchange set, see `Catalog::open_exchange_set`.
+#[derive(Debug, Clone)]
+pub struct ExchangeSetFile {
+    /// The catalog record of the file.
+    pub record: Record,
+    /// The absolute path of the file, see `resolve_file`.
+    pub path: PathBuf,
+    /// The implementation from `CATD/IMPL`, see `Record::implementation`.
+    pub implementation: Option<FileImplementation>,
+    /// The extent as (south, west, north, east), None for files without one, e.g. text files.
+    pub bbox: Option<(f64, f64, f64, f64)>,
+}
+
+/// The catalog of an exchange set with the paths of the files it references resolved, see
+/// `Catalog::open_exchange_set`.
+#[derive(Debug, Clone)]
+pub struct ExchangeSet {
+    root: PathBuf,
+    files: Vec<ExchangeSetFile>,
+}
+
+impl ExchangeSet {
+    /// The absolute path of the root folder of the exchange set, which holds the catalog.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The files referenced by the catalog, in the order of the catalog. This includes the catalog
+    /// itself and text files, records without a `FILE` are left out.
+    pub fn files(&self) -> &[ExchangeSetFile] {
+        &self.files
+    }
+
+    /// The cells of the exchange set, i.e. the files in binary implementation.
+    pub fn cells(&self) -> impl Iterator<Item = &ExchangeSetFile> {
+        self.files
+            .iter()
+            .filter(|file| file.implementation == Some(FileImplementation::Binary))
+    }
+}
+
+/// Converts a coordinate stored as a scaled integer to degrees using the Coordinate
+/// Multiplication Factor (COMF). The same goes for soundings and the SOMF.
+pub fn apply_comf(raw: i64, comf: u32) -> f64 {
+    raw as f64 / f64::from(comf)
+}
+
+// A subfield that has to fit a u32, e.g. a multiplication factor
+pub(crate) fn to_u32(subfield: &str, value: i64) -> Result<u32> {
+    u32::try_from(value).map_err(|_| {
+        ErrorKind::BadAttribute(format!("{} out of range: {}", subfield, value)).into()
+    })
+}
+
+/// Checks a coordinate in degrees, e.g. from `apply_comf`, and wraps the longitude into
+/// [-180, 180]. A latitude outside of [-90, 90] or a coordinate that is not finite is an error,
+/// as it points at a wrong COMF.
+pub fn normalize_lonlat(lon: f64, lat: f64) -> Result<(f64, f64)> {
+    if !lon.is_finite() || !lat.is_finite() || !(-90.0..=90.0).contains(&lat) {
+        return Err(ErrorKind::CoordinateOutOfRange { lon, lat }.into());
+    }
+    if (-180.0..=180.0).contains(&lon) {
+        return Ok((lon, lat));
+    }
+    Ok(((lon + 180.0).rem_euclid(360.0) - 180.0, lat))
+}
+
+/// Verifies a file referenced by a catalog record against the CRC in its CATD/CRCS subfield,
+/// which is stored as 8 hexadecimal characters.
+pub fn verify_crc(record: &Record, file_bytes: &[u8]) -> Result<bool> {
+    let crcs = record
+        .get_str(CATD, "CRCS")
+        .filter(|crcs| !crcs.is_empty())
+        .ok_or(ErrorKind::MissingCrc)?;
+    if crcs.len() != 8 {
+        return Err(ErrorKind::BadCrc(crcs.to_string()).into());
+    }
+    let expected =
+        u32::from_str_radix(crcs, 16).with_context(|_| ErrorKind::BadCrc(crcs.to_string()))?;
+    Ok(crc32(file_bytes) == expected)
+}
+
+// The CRC-32 of ISO 3309 / IEEE 802.3, which is the one used by S-57
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &b in bytes {
+        crc ^= u32::from(b);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+// The 64 bit FNV-1a hash, which unlike the hasher of the standard library is stable between
+// releases
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Fnv1a {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = (self.0 ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl<R: Read> Catalog<R> {
+    pub fn new(rdr: R) -> Result<Catalog<R>> {
+        CatalogOptions::default().open(rdr)
+    }
+
+    /// Opens a file whose DDR is the same as the one of the schema, e.g. another cell of an
+    /// exchange set. The DDR of the reader is skipped without parsing it, so the records are
+    /// parsed with the schema.
+    pub fn with_schema(schema: CatalogSchema, mut rdr: R) -> Result<Catalog<R>> {
+        let mut buf = Vec::new();
+        let length = skip_record(&mut rdr, &mut buf).context(ErrorKind::CouldNotParseCatalog)?;
+        Ok(Catalog {
+            schema,
+            rdr,
+            pos: length as u64,
+            first: length as u64,
+            index: HashMap::new(),
+            start: None,
+            buf,
+            warnings: Vec::new(),
+            parsed: (0, 0),
+            peeked: None,
+            pending: Vec::new(),
+        })
+    }
+
+    /// A copy of the schema of the catalog, i.e. `schema().clone()`, to open other files with the
+    /// same DDR, see `Catalog::with_schema`.
+    pub fn clone_schema(&self) -> CatalogSchema {
+        self.schema.clone()
+    }
+
+    /// The schema of the catalog, i.e. its parsed DDR.
+    pub fn schema(&self) -> &CatalogSchema {
+        &self.schema
+    }
+
+    /// The interchange level from the leader of the DDR.
+    pub fn interchange_level(&self) -> Result<InterchangeLevel> {
+        self.schema.interchange_level()
+    }
+
+    /// The application indicator from the leader of the DDR, a blank for S-57.
+    pub fn application_indicator(&self) -> char {
+        self.schema.application_indicator()
+    }
+
+    /// Iterates over the fields declared in the DDR as (field tag, schema) pairs.
+    pub fn fields(&self) -> impl Iterator<Item = (&str, FieldSchema<'_>)> {
+        self.schema.fields()
+    }
+
+    /// The field tag pairs from the File Control Field as (parent, child). Together they describe
+    /// the tree of fields below the record identifier field `0001`.
+    pub fn field_tag_pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.schema.field_tag_pairs()
+    }
+
+    /// The File Control Field of the DDR, the tree of the fields in a record.
+    pub fn file_control(&self) -> FileControl<'_> {
+        self.schema.file_control()
+    }
+
+    /// The number of fields of the DDR and the lengths of its directory and field area.
+    pub fn ddr_stats(&self) -> DdrStats {
+        self.schema.ddr.stats()
+    }
+
+    /// A fingerprint of the schema declared in the DDR, e.g. to invalidate cached catalogs. It
+    /// covers the field tags with their array descriptors and format controls, and is the same for
+    /// catalogs with identical DDRs regardless of the order of their directories.
+    pub fn schema_digest(&self) -> u64 {
+        let mut fields = self.fields().collect::<Vec<(&str, FieldSchema<'_>)>>();
+        fields.sort_by_key(|(tag, _)| *tag);
+        let mut digest = Fnv1a::new();
+        for (tag, schema) in fields {
+            digest.write(tag.as_bytes());
+            for (label, pd) in &schema.0.foc {
+                digest.write(&[UNIT_SEPARATOR]);
+                digest.write(label.as_bytes());
+                digest.write(b"=");
+                digest.write(pd.to_string().as_bytes());
+            }
+            digest.write(&[RECORD_SEPARATOR]);
+        }
+        digest.finish()
+    }
+
+    /// Checks the DDR against the S-57 catalog profile, i.e. a `0001` field and a `CATD` field
+    /// with the array descriptors RCNM, RCID, FILE, LFIL, VOLM, IMPL, SLAT, WLON, NLAT, ELON, CRCS
+    /// and COMT. The error lists the missing and unexpected fields, with subfields as `CATD.CRCS`.
+    /// The optional cross reference field `CATX` is not checked.
+    pub fn validate(&self) -> Result<()> {
+        let fields = &self.schema.ddr.data_descriptive_fields;
+        let mut missing = Vec::new();
+        let mut unexpected = fields
+            .keys()
+            .filter(|tag| ![TOPLVL, CATD, CATX].contains(&tag.as_str()))
+            .cloned()
+            .collect::<Vec<String>>();
+        unexpected.sort();
+        if !fields.contains_key(TOPLVL) {
+            missing.push(TOPLVL.to_string());
+        }
+        match fields.get(CATD) {
+            Some(catd) => {
+                let descriptors = FieldSchema(catd).array_descriptors().collect::<Vec<&str>>();
+                missing.extend(
+                    CATD_DESCRIPTORS
+                        .iter()
+                        .filter(|desc| !descriptors.contains(desc))
+                        .map(|desc| format!("{}.{}", CATD, desc)),
+                );
+                unexpected.extend(
+                    descriptors
+                        .iter()
+                        .filter(|desc| !CATD_DESCRIPTORS.contains(desc))
+                        .map(|desc| format!("{}.{}", CATD, desc)),
+                );
+            }
+            None => missing.push(CATD.to_string()),
+        }
+        if missing.is_empty() && unexpected.is_empty() {
+            Ok(())
+        } else {
+            Err(ErrorKind::InvalidCatalogProfile {
+                missing,
+                unexpected,
+            }
+            .into())
+        }
+    }
+
+    /// Parses the remaining records and yields only those whose CATD extent (SLAT, WLON, NLAT,
+    /// ELON) intersects the given box. Records without an extent are skipped. A box where west is
+    /// greater than east crosses the antimeridian.
+    pub fn within_bbox(
+        &mut self,
+        south: f64,
+        west: f64,
+        north: f64,
+        east: f64,
+    ) -> impl Iterator<Item = Result<Record>> + '_ {
+        self.filter(move |record| match record {
+            Ok(record) => record_bbox(record)
+                .is_some_and(|bbox| bbox_intersects(bbox, (south, west, north, east))),
+            Err(_) => true,
+        })
+    }
+
+    /// Consumes the catalog and yields only the records holding the given field, e.g. `CATD`.
+    /// Errors are passed through.
+    pub fn records_by_field<'a>(self, tag: &'a str) -> impl Iterator<Item = Result<Record>> + 'a
+    where
+        R: 'a,
+    {
+        self.filter(move |record| match record {
+            Ok(record) => record.get(tag).is_some(),
+            Err(_) => true,
+        })
+    }
+
+    /// Consumes the catalog and yields the remaining records transformed by `f`, e.g. to their
+    /// `FILE` subfields. Errors are passed through.
+    pub fn map_records<T, F>(self, mut f: F) -> impl Iterator<Item = Result<T>>
+    where
+        F: FnMut(Record) -> T,
+    {
+        self.map(move |record| record.map(&mut f))
+    }
+
+    /// Parses the remaining records and returns the distinct volume labels of the files they
+    /// reference, see `Record::volume`. A multi-volume exchange set is complete when all of them
+    /// are present.
+    pub fn volumes(&mut self) -> Result<BTreeSet<String>> {
+        let mut volumes = BTreeSet::new();
+        for record in self {
+            if let Some(volume) = record?.volume() {
+                volumes.insert(volume.to_string());
+            }
+        }
+        Ok(volumes)
+    }
+
+    /// The nonfatal deviations found in the records parsed so far.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Consumes the catalog and returns the reader, positioned after the last record read, or
+    /// after the record read ahead by `peek_buffered`.
+    pub fn into_reader(self) -> R {
+        self.rdr
+    }
+
+    /// Turns the catalog into an iterator that skips corrupt records instead of yielding errors,
+    /// see `LenientCatalog`.
+    pub fn lenient(self) -> LenientCatalog<R> {
+        LenientCatalog {
+            catalog: self,
+            pending: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Parses only the given (field, subfield) pairs of the remaining records, e.g.
+    /// `[("CATD", "FILE")]`. The other subfields are skipped without being decoded and fields
+    /// without a requested subfield are left out of the records. The bytes of every record are
+    /// still read.
+    pub fn project<'a>(
+        &'a mut self,
+        subfields: &'a [(&'a str, &'a str)],
+    ) -> impl Iterator<Item = Result<Record>> + 'a {
+        std::iter::from_fn(move || self.parse_projected_dr(Some(subfields)).transpose())
+    }
+
+    /// Like `Catalog::peek`, but for readers that can not seek. The bytes of the next record are
+    /// read ahead and kept, so the following `next` returns the same record, or parses the same
+    /// bytes again after an error. The position is not advanced.
+    pub fn peek_buffered(&mut self) -> Option<Result<&Record>> {
+        if self.peeked.is_some() {
+            return self.peeked.as_ref().map(Ok);
+        }
+        let prior = std::mem::take(&mut self.pending);
+        let mut tee = Tee {
+            rdr: Read::chain(&prior[..], &mut self.rdr),
+            copy: Vec::new(),
+        };
+        let warnings = self.warnings.len();
+        let record = parse_projected_dr(
+            &mut tee,
+            &self.schema.ddr,
+            &mut self.buf,
+            self.pos,
+            &self.schema.options,
+            &mut self.warnings,
+            None,
+        );
+        let Tee { rdr, mut copy } = tee;
+        copy.extend_from_slice(rdr.into_inner().0);
+        self.pending = copy;
+        self.finish_peek(record, warnings)
+    }
+
+    // Keeps the peeked record, the warnings of a record are kept as next does not parse it again
+    fn finish_peek(
+        &mut self,
+        record: Result<Option<(Record, usize)>>,
+        warnings: usize,
+    ) -> Option<Result<&Record>> {
+        match record {
+            Ok(Some((record, _))) => Some(Ok(self.peeked.insert(record))),
+            Ok(None) => {
+                self.warnings.truncate(warnings);
+                None
+            }
+            Err(err) => {
+                self.warnings.truncate(warnings);
+                Some(Err(err))
+            }
+        }
+    }
+
+    fn parse_dr(&mut self) -> Result<Option<Record>> {
+        // A peeked record is not parsed again, its bytes are skipped
+        if let Some(record) = self.peeked.take() {
+            let length = record.length;
+            let buffered = length.min(self.pending.len());
+            self.pending.drain(..buffered);
+            let skipped = buffered as u64
+                + std::io::copy(
+                    &mut (&mut self.rdr).take((length - buffered) as u64),
+                    &mut std::io::sink(),
+                )
+                .with_context(|err| ErrorKind::IOError(err.kind()))?;
+            if skipped < length as u64 {
+                return Err(ErrorKind::TruncatedRecord {
+                    expected: length,
+                    got: skipped as usize,
+                }
+                .into());
+            }
+            self.pos += length as u64;
+            self.parsed.0 += 1;
+            self.parsed.1 += length as u64;
+            return Ok(Some(record));
+        }
+        self.parse_projected_dr(None)
+    }
+
+    fn parse_projected_dr(&mut self, wanted: Projection<'_>) -> Result<Option<Record>> {
+        self.peeked = None;
+        // The bytes read ahead are read first, those that are not consumed are kept
+        let pending = std::mem::take(&mut self.pending);
+        let mut rdr = Read::chain(&pending[..], &mut self.rdr);
+        let record = parse_projected_dr(
+            &mut rdr,
+            &self.schema.ddr,
+            &mut self.buf,
+            self.pos,
+            &self.schema.options,
+            &mut self.warnings,
+            wanted,
+        );
+        self.pending = rdr.into_inner().0.to_vec();
+        match record? {
+            Some((record, length)) => {
+                self.pos += length as u64;
+                self.parsed.0 += 1;
+                self.parsed.1 += length as u64;
+                Ok(Some(record))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl Catalog<PathReader> {
+    /// Opens the catalog at the given path, see `PathReader`. Memory mapping avoids the many small
+    /// reads of large catalogs, but the file must not be modified while the catalog is open.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Catalog<PathReader>> {
+        let file = std::fs::File::open(path).with_context(|err| ErrorKind::IOError(err.kind()))?;
+        #[cfg(feature = "mmap")]
+        let rdr = {
+            // Safety: the mapping is only read, and the caller keeps the file unmodified
+            let mmap = unsafe { memmap2::Mmap::map(&file) }
+                .with_context(|err| ErrorKind::IOError(err.kind()))?;
+            Cursor::new(mmap)
+        };
+        #[cfg(not(feature = "mmap"))]
+        let rdr = std::io::BufReader::new(file);
+        Catalog::from_seekable(rdr)
+    }
+
+    /// Opens the exchange set with the given root folder, i.e. parses its `CATALOG.031` and
+    /// resolves the files it references against the folder, see `ExchangeSet`.
+    pub fn open_exchange_set<P: AsRef<Path>>(dir: P) -> Result<ExchangeSet> {
+        let root = std::fs::canonicalize(dir).with_context(|err| ErrorKind::IOError(err.kind()))?;
+        let catalog = Catalog::from_path(root.join(CATALOG_FILE))?;
+        let files = catalog
+            .filter_map(|record| {
+                let record = match record {
+                    Ok(record) => record,
+                    Err(err) => return Some(Err(err)),
+                };
+                let path = resolve_file(&record, &root)?;
+                Some(Ok(ExchangeSetFile {
+                    path,
+                    implementation: record.implementation(),
+                    bbox: record_bbox(&record),
+                    record,
+                }))
+            })
+            .collect::<Result<Vec<ExchangeSetFile>>>()?;
+        Ok(ExchangeSet { root, files })
+    }
+}
+
+impl<R: Read + Seek> Catalog<R> {
+    /// Like `Catalog::new` but also records the absolute position of the first Data Record, so
+    /// that seeking does not depend on how many bytes have been read so far, which is uncertain
+    /// after a parse error.
+    pub fn from_seekable(rdr: R) -> Result<Catalog<R>> {
+        Catalog::new(rdr)?.record_start()
+    }
+
+    /// Like `Catalog::with_schema` but also records the absolute position of the first Data
+    /// Record, see `Catalog::from_seekable`.
+    pub fn with_schema_seekable(schema: CatalogSchema, rdr: R) -> Result<Catalog<R>> {
+        Catalog::with_schema(schema, rdr)?.record_start()
+    }
+
+    // Records the current position of the reader as the position of the first Data Record
+    fn record_start(mut self) -> Result<Catalog<R>> {
+        let start = self
+            .rdr
+            .stream_position()
+            .with_context(|err| ErrorKind::IOError(err.kind()))?;
+        self.start = Some(start);
+        Ok(self)
+    }
+
+    /// Parses the next record without consuming it, i.e. the following `next` returns the same
+    /// record. The reader is put back to the exact position it had before, also after a parse
+    /// error, so the position is not advanced either. The record is kept until `next` or a seek, an
+    /// error is returned again by `next`. For readers that can not seek, use
+    /// `Catalog::peek_buffered` instead.
+    pub fn peek(&mut self) -> Option<Result<&Record>> {
+        if self.peeked.is_some() || !self.pending.is_empty() {
+            return self.peek_buffered();
+        }
+        let at = match self.rdr.stream_position() {
+            Ok(at) => at,
+            Err(err) => return Some(Err(ErrorKind::IOError(err.kind()).into())),
+        };
+        let warnings = self.warnings.len();
+        let record = parse_projected_dr(
+            &mut self.rdr,
+            &self.schema.ddr,
+            &mut self.buf,
+            self.pos,
+            &self.schema.options,
+            &mut self.warnings,
+            None,
+        );
+        if let Err(err) = self.rdr.seek(SeekFrom::Start(at)) {
+            return Some(Err(ErrorKind::IOError(err.kind()).into()));
+        }
+        self.finish_peek(record, warnings)
+    }
+
+    /// Rewinds to the first Data Record without parsing the DDR again.
+    pub fn reset(&mut self) -> Result<()> {
+        self.seek_to(self.first)
+    }
+
+    /// Builds an index from record id to the position of the record in the file, used by
+    /// `seek_to_record`. Note that this parses every Data Record, i.e. it consumes a full pass over
+    /// the file. The reader is put back where it was afterwards, so iteration is not affected.
+    pub fn build_index(&mut self) -> Result<()> {
+        let current = self.pos;
+        self.reset()?;
+        let mut index = HashMap::new();
+        loop {
+            let offset = self.pos;
+            match self.parse_dr()? {
+                Some(record) => {
+                    if let Some(id) = record.id() {
+                        index.insert(id, offset);
+                    }
+                }
+                None => break,
+            }
+        }
+        self.index = index;
+        self.seek_to(current)
+    }
+
+    /// Counts all Data Records by reading only the record lengths and seeking past the rest, which
+    /// is much cheaper than counting the parsed records. Padding after the last record ends the
+    /// count like the end of the file. The reader is put back where it was.
+    pub fn count_records(&mut self) -> Result<usize> {
+        let current = self.pos;
+        self.reset()?;
+        let mut count = 0;
+        let mut padding = Vec::new();
+        while let Some(length) = read_record_length(&mut self.rdr, &mut padding)? {
+            self.pos += 5;
+            self.seek_to(self.pos + length as u64 - 5)?;
+            count += 1;
+        }
+        self.pos += padding.len() as u64;
+        self.seek_to(current)?;
+        Ok(count)
+    }
+
+    /// Seeks to and parses the record with the given id. Returns None if the id is not in the
+    /// index, so `build_index` must be called first. Iteration continues after the found record.
+    pub fn seek_to_record(&mut self, id: i64) -> Result<Option<Record>> {
+        match self.index.get(&id) {
+            Some(&offset) => {
+                self.seek_to(offset)?;
+                self.parse_dr()
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// An estimate of the number of Data Records left, for progress reporting. It is the number of
+    /// bytes left in the reader divided by the average length of the records parsed so far, so it
+    /// is None before the first record is parsed. The reader is put back where it was.
+    pub fn estimated_remaining(&mut self) -> Result<Option<usize>> {
+        let (count, bytes) = self.parsed;
+        if count == 0 || bytes == 0 {
+            return Ok(None);
+        }
+        let current = self
+            .rdr
+            .stream_position()
+            .with_context(|err| ErrorKind::IOError(err.kind()))?;
+        let end = self
+            .rdr
+            .seek(SeekFrom::End(0))
+            .with_context(|err| ErrorKind::IOError(err.kind()))?;
+        self.rdr
+            .seek(SeekFrom::Start(current))
+            .with_context(|err| ErrorKind::IOError(err.kind()))?;
+        let remaining = end.saturating_sub(current) + self.pending.len() as u64;
+        // Rounded to the nearest number of records
+        Ok(Some(
+            ((remaining * count as u64 + bytes / 2) / bytes) as usize,
+        ))
+    }
+
+    // Seeks to a position counted from the start of the DDR
+    fn seek_to(&mut self, pos: u64) -> Result<()> {
+        let seek = match self.start {
+            Some(start) => SeekFrom::Start(start + pos - self.first),
+            // The reader is ahead by the bytes read ahead
+            None => SeekFrom::Current(pos as i64 - self.pos as i64 - self.pending.len() as i64),
+        };
+        self.rdr
+            .seek(seek)
+            .with_context(|err| ErrorKind::IOError(err.kind()))?;
+        self.pos = pos;
+        self.peeked = None;
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+// The extent of a catalog record as (south, west, north, east)
+pub(crate) fn record_bbox(record: &Record) -> Option<(f64, f64, f64, f64)> {
+    Some((
+        record.get_f64(CATD, "SLAT")?,
+        record.get_f64(CATD, "WLON")?,
+        record.get_f64(CATD, "NLAT")?,
+        record.get_f64(CATD, "ELON")?,
+    ))
+}
+
+// Splits a longitude range crossing the antimeridian into two ranges
+fn lon_ranges(west: f64, east: f64) -> Vec<(f64, f64)> {
+    if west <= east {
+        vec![(west, east)]
+    } else {
+        vec![(west, 180.0), (-180.0, east)]
+    }
+}
+
+fn bbox_intersects(a: (f64, f64, f64, f64), b: (f64, f64, f64, f64)) -> bool {
+    let (a_south, a_west, a_north, a_east) = a;
+    let (b_south, b_west, b_north, b_east) = b;
+    if a_south > b_north || b_south > a_north {
+        return false;
+    }
+    lon_ranges(a_west, a_east).iter().any(|&(aw, ae)| {
+        lon_ranges(b_west, b_east)
+            .iter()
+            .any(|&(bw, be)| aw <= be && bw <= ae)
+    })
+}
+
+impl<'a> TryFrom<&'a [u8]> for Catalog<Cursor<&'a [u8]>> {
+    type Error = Error;
+    fn try_from(bytes: &'a [u8]) -> Result<Catalog<Cursor<&'a [u8]>>> {
+        Catalog::new(Cursor::new(bytes))
+    }
+}
+
+impl<R: Read> Iterator for Catalog<R> {
+    type Item = Result<Record>;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.parse_dr() {
+            Ok(Some(dr)) => Some(Ok(dr)),
+            Ok(None) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+/// A lexical level to decode all strings with, see `CatalogOptions::lexical_level`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LexicalLevel {
+    /// ASCII.
+    Level0,
+    /// ISO 8859-1.
+    Level1,
+    /// UCS-2.
+    Level2,
+}
+
+impl From<LexicalLevel> for TruncEscSeq {
+    fn from(level: LexicalLevel) -> TruncEscSeq {
+        match level {
+            LexicalLevel::Level0 => TruncEscSeq::LE0,
+            LexicalLevel::Level1 => TruncEscSeq::LE1,
+            LexicalLevel::Level2 => TruncEscSeq::LE2,
+        }
+    }
+}
+
+/// Options for parsing a catalog, `Catalog::new` uses the defaults.
+///
+/// Lenient mode is not an option but the separate entry point `CatalogOptions::open_lenient`. The
+/// lenient catalog is another type than `Catalog`, as it resynchronizes after a corrupt record
+/// and collects the errors of the skipped records instead of yielding them, so `open` can not
+/// return it depending on a flag.
+///
+/// ```no_run
+/// # use rust_s57::catalog::{CatalogOptions, LexicalLevel};
+/// let file = std::fs::File::open("CATALOG.031").unwrap();
+/// let catalog = CatalogOptions::new()
+///     .lexical_level(LexicalLevel::Level1)
+///     .open(file)
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct CatalogOptions {
+    lexical_level: Option<LexicalLevel>,
+    strict_terminators: bool,
+    record_id_name: Option<String>,
+    strict_field_lengths: bool,
+    retain_raw_fields: bool,
+}
+
+impl Default for CatalogOptions {
+    fn default() -> CatalogOptions {
+        CatalogOptions {
+            lexical_level: None,
+            strict_terminators: true,
+            record_id_name: None,
+            strict_field_lengths: false,
+            retain_raw_fields: false,
+        }
+    }
+}
+
+impl CatalogOptions {
+    pub fn new() -> CatalogOptions {
+        CatalogOptions::default()
+    }
+
+    /// Decodes the strings of all fields with the given lexical level instead of the one declared
+    /// by each field in the DDR, for producers that declare the wrong one.
+    pub fn lexical_level(mut self, level: LexicalLevel) -> CatalogOptions {
+        self.lexical_level = Some(level);
+        self
+    }
+
+    /// Whether a field of a Data Record that does not end with a record separator is an error,
+    /// which is the default. Otherwise the field is parsed as it is.
+    pub fn strict_terminators(mut self, strict: bool) -> CatalogOptions {
+        self.strict_terminators = strict;
+        self
+    }
+
+    /// Whether a field whose subfields do not take up exactly the length of the field given by the
+    /// directory is an error, which points at a desynchronized parse. Off by default, then the
+    /// bytes after the last subfield are ignored.
+    pub fn strict_field_lengths(mut self, strict: bool) -> CatalogOptions {
+        self.strict_field_lengths = strict;
+        self
+    }
+
+    /// Whether the records keep a copy of the bytes of their fields as read, for
+    /// `Record::field_reader`. Off by default, which saves a copy per record.
+    pub fn retain_raw_fields(mut self, retain: bool) -> CatalogOptions {
+        self.retain_raw_fields = retain;
+        self
+    }
+
+    /// The name of the unnamed subfield of the record identifier field `0001` in the fields of the
+    /// records, `DRID` by default.
+    pub fn record_id_name(mut self, name: &str) -> CatalogOptions {
+        self.record_id_name = Some(name.to_string());
+        self
+    }
+
+    /// Parses the DDR and returns the catalog, ready to iterate over the Data Records.
+    pub fn open<R: Read>(&self, mut rdr: R) -> Result<Catalog<R>> {
+        let mut ddr = parse_ddr(&mut rdr).context(ErrorKind::CouldNotParseCatalog)?;
+        self.apply_to_ddr(&mut ddr);
+        let pos = ddr.length() as u64;
+        Ok(Catalog {
+            schema: CatalogSchema {
+                ddr,
+                options: self.clone(),
+            },
+            rdr,
+            pos,
+            first: pos,
+            index: HashMap::new(),
+            start: None,
+            buf: Vec::new(),
+            warnings: Vec::new(),
+            parsed: (0, 0),
+            peeked: None,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Like `CatalogOptions::open`, but returns a catalog that skips corrupt records, see
+    /// `LenientCatalog`. This takes the place of a `lenient` option, see `CatalogOptions`.
+    pub fn open_lenient<R: Read>(&self, rdr: R) -> Result<LenientCatalog<R>> {
+        Ok(self.open(rdr)?.lenient())
+    }
+
+    pub(crate) fn apply_to_ddr(&self, ddr: &mut DDR) {
+        if let Some(level) = self.lexical_level {
+            for entry in ddr.data_descriptive_fields.values_mut() {
+                entry.fic.tes = level.into();
+            }
+        }
+        if let Some(name) = &self.record_id_name {
+            for entry in ddr.data_descriptive_fields.values_mut() {
+                for (label, _) in entry.foc.iter_mut().filter(|(label, _)| label == DRID) {
+                    label.clone_from(name);
+                }
+            }
+        }
+    }
+}
+
+/// An iterator over the records of a catalog that skips corrupt records. After a record fails to
+/// parse it resynchronizes by scanning for the next byte sequence that parses as the leader of a
+/// Data Record. The errors of the skipped records are collected, see `LenientCatalog::errors`.
+#[derive(Debug)]
+pub struct LenientCatalog<R: Read> {
+    catalog: Catalog<R>,
+    pending: Vec<u8>, // bytes read ahead from the reader of the catalog, starting at catalog.pos
+    errors: Vec<Error>,
+}
+
+impl<R: Read> LenientCatalog<R> {
+    /// The errors of the records skipped so far.
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    fn consume(&mut self, len: usize) {
+        self.pending.drain(..len);
+        self.catalog.pos += len as u64;
+    }
+
+    // Reads ahead until at least len bytes are pending. Returns false at EOF.
+    fn fill(&mut self, len: usize) -> Result<bool> {
+        let mut chunk = [0; 4096];
+        while self.pending.len() < len {
+            match read_fully(&mut self.catalog.rdr, &mut chunk)? {
+                0 => return Ok(false),
+                n => self.pending.extend_from_slice(&chunk[..n]),
+            }
+        }
+        Ok(true)
+    }
+
+    // The record length if bytes start with the leader of a Data Record
+    fn record_length(bytes: &[u8]) -> Option<usize> {
+        if bytes.len() < LEADER_LENGTH {
+            return None;
+        }
+        let length = parse_to_usize(&bytes[..5]).ok()?;
+        match parse_leader(&bytes[5..24], length) {
+            Ok(leader)
+                if (leader.li == 'D' || leader.li == 'R') && leader.ba as usize <= length =>
+            {
+                Some(length)
+            }
+            _ => None,
+        }
+    }
+
+    // Parses the record at the start of pending, or None if there is none
+    fn next_record(&mut self) -> Result<Option<(Record, usize)>> {
+        if !self.fill(LEADER_LENGTH)? {
+            return match self.pending.len() {
+                0 => Ok(None),
+                got => Err(ErrorKind::TruncatedRecord {
+                    expected: LEADER_LENGTH,
+                    got,
+                }
+                .into()),
+            };
+        }
+        let length = Self::record_length(&self.pending).ok_or(ErrorKind::InvalidLeader)?;
+        if !self.fill(length)? {
+            return Err(ErrorKind::TruncatedRecord {
+                expected: length,
+                got: self.pending.len(),
+            }
+            .into());
+        }
+        let mut bytes = &self.pending[..length];
+        match parse_dr(
+            &mut bytes,
+            &self.catalog.schema.ddr,
+            &mut self.catalog.buf,
+            self.catalog.pos,
+            &self.catalog.schema.options,
+            &mut self.catalog.warnings,
+        )? {
+            Some((record, _)) => Ok(Some((record, length))),
+            None => Err(ErrorKind::InvalidDR.into()),
+        }
+    }
+
+    // Whether the bytes after a record of the given length start another record or end the file
+    fn followed_by_record(&mut self, length: usize) -> Result<bool> {
+        if !self.fill(length + LEADER_LENGTH)? {
+            return Ok(self.pending.len() == length);
+        }
+        Ok(Self::record_length(&self.pending[length..]).is_some())
+    }
+}
+
+impl<R: Read> Iterator for LenientCatalog<R> {
+    type Item = Record;
+    fn next(&mut self) -> Option<Record> {
+        let mut resyncing = false;
+        loop {
+            let err = match self.next_record() {
+                Ok(Some((record, length))) => {
+                    self.consume(length);
+                    return Some(record);
+                }
+                Ok(None) => return None,
+                Err(err) => err,
+            };
+            if !resyncing {
+                self.errors.push(err);
+                resyncing = true;
+            }
+            // Continue after the failed record if its length can be trusted, otherwise scan from
+            // the next byte
+            let length = Self::record_length(&self.pending).unwrap_or(1);
+            match self.followed_by_record(length) {
+                Ok(true) => {
+                    self.consume(length);
+                }
+                Ok(false) if self.pending.is_empty() => return None,
+                Ok(false) => {
+                    self.consume(1);
+                }
+                Err(err) => {
+                    self.errors.push(err);
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+// A reader that keeps a copy of the bytes read, to read them again
+struct Tee<R: Read> {
+    rdr: R,
+    copy: Vec<u8>,
+}
+
+impl<R: Read> Read for Tee<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.rdr.read(buf)?;
+        self.copy.extend_from_slice(&buf[..read]);
+        Ok(read)
+    }
+}
+
+// Reads until buf is full or the reader is at EOF, since a single read may return fewer bytes
+// than asked for, e.g. from a socket. Interrupted reads are retried, a read that would block is
+// returned as an IOError rather than polled. Returns the number of bytes read.
+fn read_fully<R: Read>(rdr: &mut R, buf: &mut [u8]) -> Result<usize> {
+    let mut read = 0;
+    while read < buf.len() {
+        match rdr.read(&mut buf[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(ref err) if err.kind() == std::io::ErrorKind::Interrupted => (),
+            Err(err) => return Err(ErrorKind::IOError(err.kind()).into()),
+        }
+    }
+    Ok(read)
+}
+
+// Like read_to_end, but with the retries of read_fully
+fn read_to_end<R: Read>(rdr: &mut R, buf: &mut Vec<u8>) -> Result<()> {
+    let mut chunk = [0; 512];
+    loop {
+        match read_fully(rdr, &mut chunk)? {
+            0 => return Ok(()),
+            n => buf.extend_from_slice(&chunk[..n]),
+        }
+    }
+}
+
+// Reads one record into buf without parsing it and returns its length
+fn skip_record<R: Read>(rdr: &mut R, buf: &mut Vec<u8>) -> Result<usize> {
+    let mut len_bytes = [0; 5];
+    let got = read_fully(rdr, &mut len_bytes)?;
+    if got < 5 {
+        return Err(ErrorKind::TruncatedRecord { expected: 5, got }.into());
+    }
+    let length = parse_to_usize(&len_bytes)?;
+    if length < LEADER_LENGTH {
+        return Err(ErrorKind::InvalidLeader.into());
+    }
+    buf.clear();
+    buf.resize(length - 5, 0);
+    let read = read_fully(rdr, buf)?;
+    if read < buf.len() {
+        return Err(ErrorKind::TruncatedRecord {
+            expected: length,
+            got: 5 + read,
+        }
+        .into());
+    }
+    Ok(length)
+}
+
+// Checks that the fields of the directory do not overlap. The entries need not be in the order of
+// the field area, whether they fit into it is checked when a field is sliced.
+fn check_directory_regions(dirs: &[DirectoryEntry]) -> Result<()> {
+    let mut regions = dirs
+        .iter()
+        .map(|dir| {
+            let end = dir.offset.checked_add(dir.length);
+            end.map(|end| (dir.offset, end))
+                .ok_or(ErrorKind::BadDirectoryData)
+        })
+        .collect::<std::result::Result<Vec<(usize, usize)>, ErrorKind>>()?;
+    regions.sort_unstable();
+    if regions.windows(2).any(|pair| pair[0].1 > pair[1].0) {
+        return Err(ErrorKind::BadDirectoryData.into());
+    }
+    Ok(())
+}
+
+// Whether the bytes are padding, i.e. blanks or NULs, which some producers append after the last
+// record
+pub(crate) fn is_padding(bytes: &[u8]) -> bool {
+    bytes.iter().all(|&b| b == b' ' || b == 0)
+}
+
+// Reads the record length, stored in the first 5 bytes of a record. None at the end of the reader,
+// or if the reader ends with padding instead of a record, which is then appended to buf.
+fn read_record_length<R: Read>(rdr: &mut R, buf: &mut Vec<u8>) -> Result<Option<usize>> {
+    let mut len_bytes = [0; 5];
+    let got = read_fully(rdr, &mut len_bytes)?;
+    if got > 0 && is_padding(&len_bytes[..got]) {
+        let start = buf.len();
+        buf.extend_from_slice(&len_bytes[..got]);
+        read_to_end(rdr, buf)?;
+        if !is_padding(&buf[start..]) {
+            return Err(ErrorKind::InvalidLeader.into());
+        }
+        return Ok(None);
+    }
+    match got {
+        0 => return Ok(None),
+        5 => (),
+        got => return Err(ErrorKind::TruncatedRecord { expected: 5, got }.into()),
+    }
+    let length = parse_to_usize(&len_bytes)?;
+    // A corrupt length shorter than the leader would underflow when reading the rest
+    if length < LEADER_LENGTH {
+        return Err(ErrorKind::InvalidLeader.into());
+    }
+    Ok(Some(length))
+}
+
+// Reads one record into buf, replacing its contents, and returns the leader, the directory and the
+// index of the field area in buf. Every record results in a couple of small reads, so wrap
+// unbuffered readers like File in a BufReader.
+//
+// At the end of the reader EOF is returned. If the reader ends with padding instead of a record,
+// EOF is returned as well, with the padding left in buf.
+fn parse_dir_and_field_area<R: Read>(
+    rdr: &mut R,
+    buf: &mut Vec<u8>,
+) -> Result<(Leader, Vec<DirectoryEntry>, usize)> {
+    buf.clear();
+    let length = match read_record_length(rdr, buf)? {
+        Some(length) => length,
+        None => return Err(ErrorKind::EOF.into()),
+    };
+
+    // Read the rest of the DDR, the reader ending before it is a truncated record, not a clean EOF
+    buf.resize(length - 5, 0);
+    let read = read_fully(rdr, buf)?;
+    if read < buf.len() {
+        return Err(ErrorKind::TruncatedRecord {
+            expected: length,
+            got: 5 + read,
+        }
+        .into());
+    }
+    let leader = parse_leader(&buf[..19], length)?;
+    // The base address counts from the start of the record, buf starts after the 5 length bytes.
+    // The directory ends right before it with a record separator.
+    let field_area_idx = match (leader.ba as usize).checked_sub(5) {
+        Some(index) if index > 19 && index <= buf.len() => index,
+        _ => return Err(ErrorKind::InvalidLeader.into()),
+    };
+    if buf[field_area_idx - 1] != RECORD_SEPARATOR {
+        return Err(ErrorKind::BadDirectoryData.into());
+    }
+    let dirs = parse_directory(&buf[19..field_area_idx - 1], &leader)?;
+    check_directory_regions(&dirs)?;
+    Ok((leader, dirs, field_area_idx))
+}
+
+// The (field, subfield) pairs to parse, None parses every subfield
+type Projection<'a> = Option<&'a [(&'a str, &'a str)]>;
+
+fn is_wanted(wanted: Projection<'_>, tag: &str, subfield: &str) -> bool {
+    wanted.is_none_or(|wanted| wanted.iter().any(|&(t, s)| t == tag && s == subfield))
+}
+
+// Parses one set of the subfields of a field, the subfields that are not wanted are skipped
+fn parse_field(
+    cur: &mut Cursor<&[u8]>,
+    dir_entry: &DirectoryEntry,
+    ddf_entry: &DDFEntry,
+    offset: u64,
+    wanted: Projection<'_>,
+) -> Result<Field> {
+    let mut field = Field::new();
+    for (name, parser) in &ddf_entry.foc {
+        let subfield_offset = offset + cur.position();
+        let context = || ErrorKind::FieldParse {
+            field: dir_entry.id.clone(),
+            subfield: name.clone(),
+            offset: subfield_offset,
+        };
+        // The field ends at a terminator, whatever follows it
+        if *parser == ParseData::Terminator {
+            break;
+        }
+        // The fill bytes of a spare are skipped
+        if parser.is_spare() || !is_wanted(wanted, &dir_entry.id, name) {
+            parser
+                .skip(&mut *cur, &ddf_entry.fic.tes)
+                .with_context(|_| context())?;
+            continue;
+        }
+        let overflow = |width| ErrorKind::NumericOverflow {
+            field: format!("{}.{}", dir_entry.id, name),
+            width,
+        };
+        // Too wide integers are rejected before reading, narrower ones may still overflow
+        if let Some(width) = parser.overflowing_width() {
+            return Err(overflow(width).into());
+        }
+        let data = match parser.parse(&mut *cur, &ddf_entry.fic.tes) {
+            Err(err) if is_int_overflow(err.kind()) => {
+                let width = (offset + cur.position() - subfield_offset) as usize;
+                return Err(overflow(width).into());
+            }
+            data => data.with_context(|_| context())?,
+        };
+        field.insert(name.clone(), data);
+    }
+    Ok(field)
+}
+
+// Whether the error is an integer too large or too small for an i64
+fn is_int_overflow(kind: &ErrorKind) -> bool {
+    use std::num::IntErrorKind;
+    match kind {
+        ErrorKind::ParseIntError(err, _) => {
+            matches!(
+                err.kind(),
+                IntErrorKind::PosOverflow | IntErrorKind::NegOverflow
+            )
+        }
+        _ => false,
+    }
+}
+
+// Returns the parsed record together with its length in bytes, offset is the position of the
+// record in the file
+pub(crate) fn parse_dr<R: Read>(
+    rdr: &mut R,
+    ddr: &DDR,
+    buf: &mut Vec<u8>,
+    offset: u64,
+    options: &CatalogOptions,
+    warnings: &mut Vec<Warning>,
+) -> Result<Option<(Record, usize)>> {
+    parse_projected_dr(rdr, ddr, buf, offset, options, warnings, None)
+}
+
+// Like parse_dr, but only the wanted subfields are parsed. Fields without a wanted subfield are
+// left out of the record.
+fn parse_projected_dr<R: Read>(
+    rdr: &mut R,
+    ddr: &DDR,
+    buf: &mut Vec<u8>,
+    offset: u64,
+    options: &CatalogOptions,
+    warnings: &mut Vec<Warning>,
+    wanted: Projection<'_>,
+) -> Result<Option<(Record, usize)>> {
+    let (leader, dirs, field_area_idx) = match parse_dir_and_field_area(rdr, buf) {
+        Ok(ok) => ok,
+        Err(err) => match err.kind() {
+            ErrorKind::EOF => {
+                if !buf.is_empty() {
+                    warnings.push(Warning::UnexpectedPadding {
+                        offset,
+                        length: buf.len(),
+                    });
+                }
+                return Ok(None);
+            }
+            _ => return Err(err),
+        },
+    };
+    // Fields are found through the directory, so the order does not matter to the parsing
+    if dirs.first().is_some_and(|dir| dir.id != TOPLVL) && dirs.iter().any(|d| d.id == TOPLVL) {
+        warnings.push(Warning::NonCanonicalFieldOrder {
+            offset,
+            tag: dirs[0].id.clone(),
+        });
+    }
+    let field_data = &buf[field_area_idx..];
+    let mut record = Record {
+        offset,
+        length: leader.rl,
+        ..Record::default()
+    };
+    for dir_entry in dirs.iter() {
+        if wanted.is_some_and(|wanted| wanted.iter().all(|&(tag, _)| tag != dir_entry.id)) {
+            continue;
+        }
+        let ddf_entry = ddr
+            .data_descriptive_fields
+            .get(&dir_entry.id)
+            .ok_or_else(|| ErrorKind::UnknownField(dir_entry.id.clone()))?;
+        let bytes = field_slice(
+            field_data,
+            dir_entry,
+            options.strict_terminators,
+            &ddf_entry.fic.tes,
+        )?;
+        // The offset of the field counted from the start of the record
+        let offset = u64::from(leader.ba) + dir_entry.offset as u64;
+        let mut cur = Cursor::new(bytes);
+        let field = if ddf_entry.repeating {
+            let mut elements = Vec::new();
+            while (cur.position() as usize) < bytes.len() {
+                let start = cur.position();
+                elements.push(parse_field(&mut cur, dir_entry, ddf_entry, offset, wanted)?);
+                // Subfields without width would repeat forever
+                if cur.position() == start {
+                    return Err(ErrorKind::InvalidDR.into());
+                }
+            }
+            let labels = FieldSchema(ddf_entry)
+                .array_descriptors()
+                .collect::<Vec<&str>>();
+            let mut field = Field::new();
+            field.insert(labels.join("!"), Data::Array(elements));
+            field
+        } else {
+            parse_field(&mut cur, dir_entry, ddf_entry, offset, wanted)?
+        };
+        if options.strict_field_lengths && cur.position() as usize != bytes.len() {
+            // The field terminator is not part of bytes
+            let terminator = dir_entry.length - bytes.len();
+            return Err(ErrorKind::FieldLengthMismatch {
+                field: dir_entry.id.clone(),
+                expected: dir_entry.length,
+                consumed: cur.position() as usize + terminator,
+            }
+            .into());
+        }
+        record.insert(dir_entry.id.clone(), field);
+        if options.retain_raw_fields {
+            let span = (dir_entry.offset, dir_entry.offset + bytes.len());
+            record.spans.insert(dir_entry.id.clone(), span);
+        }
+    }
+    if options.retain_raw_fields {
+        record.raw = field_data.to_vec();
+    }
+    Ok(Some((record, leader.rl)))
+}
+
+pub(crate) fn parse_ddr<R: Read>(rdr: &mut R) -> Result<DDR> {
+    let mut buf = Vec::new();
+    let (leader, dirs, field_area_idx) = parse_dir_and_field_area(rdr, &mut buf)?;
+    let field_area = &buf[field_area_idx..];
+    let fcl = leader.field_control_length()?;
+    let file_control_field = parse_fcf(field_area, &dirs, fcl).context(ErrorKind::InvalidDDR)?;
+    let mut data_descriptive_fields =
+        parse_ddfs(field_area, &dirs, fcl).context(ErrorKind::InvalidDDR)?;
+    // Blank field controls leave the character set to the leader, an unknown one is ignored
+    if let Ok(set) = leader.character_set() {
+        for entry in data_descriptive_fields.values_mut() {
+            if entry.fic.tes == TruncEscSeq::LE0 {
+                entry.fic.tes = set.lexical_level().into();
+            }
+        }
+    }
+
+    Ok(DDR {
+        leader,
+        dirs,
+        file_control_field,
+        data_descriptive_fields,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_test_leader() -> Leader {
+        Leader {
+            rl: 241,
+            il: '3',
+            li: 'L',
+            cei: 'E',
+            vn: '1',
+            ai: ' ',
+            fcl: ['0', '9'],
+            ba: 58,
+            csi: [' ', '!', ' '],
+            flf: 3,
+            fpf: 4,
+            rsv: '0',
+            ftf: 4,
+        }
+    }
+
+    fn get_test_directory() -> Vec<DirectoryEntry> {
+        vec![
+            DirectoryEntry {
+                id: "0000".to_string(),
                 length: 19,
                 offset: 0,
             },
@@ -466,6 +2786,282 @@ mod test {
         ]
     }
 
+    fn get_test_record() -> Record {
+        let mut catd = Field::new();
+        catd.insert("FILE".to_string(), Data::String("CATALOG.031".to_string()));
+        catd.insert("RCID".to_string(), Data::Integer(Some(1)));
+        catd.insert("SLAT".to_string(), Data::Float(Some(10.0)));
+        catd.insert("NLAT".to_string(), Data::Float(None));
+        let mut record = Record::default();
+        record.insert("CATD".to_string(), catd);
+        record
+    }
+
+    #[test]
+    fn test_record_display() {
+        let mut record = get_test_record();
+        let mut first = Field::new();
+        first.insert("NAME".to_string(), Data::Binary(vec![0x6e, 0x01, 0xff]));
+        first.insert("ORNT".to_string(), Data::Integer(Some(1)));
+        let mut second = Field::new();
+        second.insert("NAME".to_string(), Data::Binary(vec![0x6e, 0x02, 0x00]));
+        second.insert("ORNT".to_string(), Data::Integer(None));
+        let mut fspt = Field::new();
+        fspt.insert("NAME!ORNT".to_string(), Data::Array(vec![first, second]));
+        record.insert("FSPT".to_string(), fspt);
+        assert_eq!(
+            record.to_string(),
+            "CATD\n  FILE: CATALOG.031\n  NLAT: \n  RCID: 1\n  SLAT: 10\n\
+             FSPT\n  [0]\n    NAME: 6E01FF\n    ORNT: 1\n  [1]\n    NAME: 6E0200\n    ORNT: \n"
+        );
+    }
+
+    #[test]
+    fn test_record_kind() {
+        let record = |tags: &[&str]| {
+            let mut record = Record::default();
+            for tag in tags {
+                record.insert(tag.to_string(), Field::new());
+            }
+            record
+        };
+        assert_eq!(
+            record(&[TOPLVL, "DSID", "DSSI"]).kind(),
+            RecordKind::Dataset
+        );
+        assert_eq!(
+            record(&[TOPLVL, "FRID", "FOID", "ATTF"]).kind(),
+            RecordKind::Feature
+        );
+        assert_eq!(record(&[TOPLVL, "VRID", "SG2D"]).kind(), RecordKind::Vector);
+        assert_eq!(record(&[TOPLVL, CATD]).kind(), RecordKind::CatalogEntry);
+        assert_eq!(
+            record(&[TOPLVL, CATX]).kind(),
+            RecordKind::CatalogCrossReference
+        );
+        assert_eq!(record(&[TOPLVL]).kind(), RecordKind::Unknown);
+        assert_eq!(get_test_record().kind(), RecordKind::CatalogEntry);
+    }
+
+    #[test]
+    fn test_record_typed_getters() {
+        let record = get_test_record();
+        assert_eq!(record.get_str("CATD", "FILE"), Some("CATALOG.031"));
+        assert_eq!(record.get_i64("CATD", "RCID"), Some(1));
+        assert_eq!(record.get_f64("CATD", "SLAT"), Some(10.0));
+    }
+
+    #[test]
+    fn test_record_typed_getters_missing_field() {
+        let record = get_test_record();
+        assert_eq!(record.get_str("DSID", "FILE"), None);
+        assert_eq!(record.get_str("CATD", "COMT"), None);
+        assert_eq!(record.get_f64("CATD", "NLAT"), None);
+    }
+
+    #[test]
+    fn test_record_typed_getters_empty_subfield() {
+        let mut id = Field::new();
+        id.insert(DRID.to_string(), Data::Integer(None));
+        let mut record = get_test_record();
+        record.insert(TOPLVL.to_string(), id);
+        record.insert(
+            "DSID".to_string(),
+            vec![("DSNM".to_string(), Data::String(String::new()))]
+                .into_iter()
+                .collect(),
+        );
+        assert_eq!(record.id(), None);
+        assert_eq!(record.get_str("DSID", "DSNM"), Some(""));
+    }
+
+    #[test]
+    fn test_record_typed_getters_wrong_type() {
+        let record = get_test_record();
+        assert_eq!(record.get_i64("CATD", "FILE"), None);
+        assert_eq!(record.get_str("CATD", "RCID"), None);
+        assert_eq!(record.get_i64("CATD", "SLAT"), None);
+    }
+
+    fn get_file_record(file: &str) -> Record {
+        let mut catd = Field::new();
+        catd.insert("FILE".to_string(), Data::String(file.to_string()));
+        let mut record = Record::default();
+        record.insert(CATD.to_string(), catd);
+        record
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_resolve_file_unix() {
+        let dir = Path::new("/data/ENC_ROOT");
+        let resolve = |file| resolve_file(&get_file_record(file), dir);
+        assert_eq!(
+            resolve("NO1CELLA.000"),
+            Some(PathBuf::from("/data/ENC_ROOT/NO1CELLA.000"))
+        );
+        assert_eq!(
+            resolve("NO\\1\\NO1CELLA.000"),
+            Some(PathBuf::from("/data/ENC_ROOT/NO/1/NO1CELLA.000"))
+        );
+        assert_eq!(
+            resolve("NO/1\\NO1CELLA.000"),
+            Some(PathBuf::from("/data/ENC_ROOT/NO/1/NO1CELLA.000"))
+        );
+        assert_eq!(
+            resolve("\\other\\NO1CELLA.000"),
+            Some(PathBuf::from("/other/NO1CELLA.000"))
+        );
+        assert_eq!(resolve(""), None);
+        assert_eq!(resolve_file(&Record::default(), dir), None);
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_resolve_file_windows() {
+        let dir = Path::new("C:\\data\\ENC_ROOT");
+        let resolve = |file| resolve_file(&get_file_record(file), dir);
+        assert_eq!(
+            resolve("NO\\1\\NO1CELLA.000"),
+            Some(PathBuf::from("C:\\data\\ENC_ROOT\\NO\\1\\NO1CELLA.000"))
+        );
+        assert_eq!(
+            resolve("NO/1\\NO1CELLA.000"),
+            Some(PathBuf::from("C:\\data\\ENC_ROOT\\NO\\1\\NO1CELLA.000"))
+        );
+        assert_eq!(
+            resolve("D:\\other\\NO1CELLA.000"),
+            Some(PathBuf::from("D:\\other\\NO1CELLA.000"))
+        );
+    }
+
+    #[test]
+    fn test_record_to_map() {
+        let mut record = get_test_record();
+        let coordinates = [(1, 2), (3, 4)]
+            .iter()
+            .map(|&(y, x)| {
+                let mut element = Field::new();
+                element.insert("YCOO".to_string(), Data::Integer(Some(y)));
+                element.insert("XCOO".to_string(), Data::Integer(Some(x)));
+                element
+            })
+            .collect();
+        let mut sg2d = Field::new();
+        sg2d.insert("YCOO!XCOO".to_string(), Data::Array(coordinates));
+        record.insert("SG2D".to_string(), sg2d);
+
+        let map = record.to_map();
+        let keys = map.keys().map(String::as_str).collect::<Vec<&str>>();
+        assert_eq!(
+            keys,
+            vec![
+                "CATD.FILE",
+                "CATD.NLAT",
+                "CATD.RCID",
+                "CATD.SLAT",
+                "SG2D.XCOO[0]",
+                "SG2D.XCOO[1]",
+                "SG2D.YCOO[0]",
+                "SG2D.YCOO[1]",
+            ]
+        );
+        assert_eq!(map["CATD.FILE"], Data::String("CATALOG.031".to_string()));
+        assert_eq!(map["SG2D.YCOO[1]"], Data::Integer(Some(3)));
+    }
+
+    fn get_crc_record(crcs: &str) -> Record {
+        let mut catd = Field::new();
+        catd.insert("CRCS".to_string(), Data::String(crcs.to_string()));
+        let mut record = Record::default();
+        record.insert("CATD".to_string(), catd);
+        record
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn test_verify_crc() {
+        let file = std::fs::read("tests/CELL.000").unwrap();
+        assert!(verify_crc(&get_crc_record("EA17DC34"), &file).unwrap());
+        assert!(verify_crc(&get_crc_record("ea17dc34"), &file).unwrap());
+        assert!(!verify_crc(&get_crc_record("EA17DC35"), &file).unwrap());
+    }
+
+    #[test]
+    fn test_verify_crc_bad_crcs() {
+        let file = std::fs::read("tests/CELL.000").unwrap();
+        assert!(verify_crc(&get_crc_record(""), &file).is_err());
+        assert!(verify_crc(&get_crc_record("EA17DC3"), &file).is_err());
+        assert!(verify_crc(&get_crc_record("EA17DC3X"), &file).is_err());
+        assert!(verify_crc(&get_test_record(), &file).is_err());
+    }
+
+    #[test]
+    fn test_normalize_lonlat() {
+        assert_eq!(normalize_lonlat(-10.25, 59.5).unwrap(), (-10.25, 59.5));
+        assert_eq!(normalize_lonlat(180.0, -90.0).unwrap(), (180.0, -90.0));
+        assert_eq!(normalize_lonlat(181.0, 0.0).unwrap(), (-179.0, 0.0));
+        assert_eq!(normalize_lonlat(-540.5, 0.0).unwrap(), (179.5, 0.0));
+        let err = normalize_lonlat(10.0, 91.0).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::CoordinateOutOfRange { .. }));
+        assert!(normalize_lonlat(f64::NAN, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_apply_comf() {
+        assert_eq!(apply_comf(593_456_789, 10_000_000), 59.3456789);
+        assert_eq!(apply_comf(-1_805_000_000, 10_000_000), -180.5);
+    }
+
+    #[test]
+    fn test_record_coordinate() {
+        let mut field = Field::new();
+        field.insert("COMF".to_string(), Data::Integer(Some(10_000_000)));
+        field.insert("SOMF".to_string(), Data::Integer(Some(10)));
+        let mut dspm = Record::default();
+        dspm.insert("DSPM".to_string(), field);
+        let comf = dspm.comf().unwrap().unwrap();
+        assert_eq!(comf, 10_000_000);
+        assert_eq!(dspm.somf().unwrap(), Some(10));
+
+        let mut field = Field::new();
+        field.insert("YCOO".to_string(), Data::Integer(Some(593_456_789)));
+        field.insert("XCOO".to_string(), Data::Integer(Some(-102_500_000)));
+        let mut sg2d = Field::new();
+        sg2d.insert("YCOO!XCOO".to_string(), Data::Array(vec![field]));
+        let mut vector = Record::default();
+        vector.insert("SG2D".to_string(), sg2d);
+        assert_eq!(vector.coordinate(comf), Some((59.3456789, -10.25)));
+        assert_eq!(dspm.coordinate(comf), None);
+    }
+
+    #[test]
+    fn test_bbox_intersects() {
+        let cell = (10.0, 10.0, 10.5, 10.5);
+        assert!(bbox_intersects(cell, (10.2, 10.2, 11.0, 11.0)));
+        assert!(!bbox_intersects(cell, (10.6, 10.0, 11.0, 10.5)));
+        assert!(!bbox_intersects(cell, (10.0, 10.6, 10.5, 11.0)));
+        // Both boxes cross the antimeridian
+        assert!(bbox_intersects(
+            (50.0, 170.0, 51.0, -170.0),
+            (50.0, 175.0, 51.0, -175.0)
+        ));
+        // One box crosses the antimeridian
+        assert!(bbox_intersects(
+            (50.0, 170.0, 51.0, -170.0),
+            (50.0, -175.0, 51.0, -160.0)
+        ));
+        assert!(!bbox_intersects(
+            (50.0, 170.0, 51.0, -170.0),
+            (50.0, -160.0, 51.0, 160.0)
+        ));
+    }
+
     #[test]
     fn test_parse_leader() {
         let length = 241;
@@ -475,6 +3071,107 @@ mod test {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_field_control_length() {
+        let leader = parse_leader("3LE1 1000058 ! 3404".as_bytes(), 241).unwrap();
+        assert_eq!(leader.field_control_length().unwrap(), 10);
+        assert_eq!(get_test_leader().field_control_length().unwrap(), 9);
+        for fcl in &["3LE1   00058 ! 3404", "3LE1 0500058 ! 3404"] {
+            let leader = parse_leader(fcl.as_bytes(), 241).unwrap();
+            assert!(leader.field_control_length().is_err());
+        }
+
+        // Field controls of 10 bytes, the last one is not interpreted
+        let ddf = "1600;&    Test Field\x1fRCNM!RCID\x1f(A(2),I(10))".as_bytes();
+        let entry = parse_ddf(ddf, 10).unwrap();
+        assert_eq!(entry.name, "Test Field");
+        assert_eq!(entry.fic.tes, TruncEscSeq::LE0);
+        assert_eq!(entry.foc.len(), 2);
+        // Field controls of 6 bytes have no truncated escape sequence
+        let entry = parse_ddf("1600;&Test Field\x1fRCNM\x1f(A(2))".as_bytes(), 6).unwrap();
+        assert_eq!(entry.name, "Test Field");
+        assert_eq!(entry.fic.tes, TruncEscSeq::LE0);
+    }
+
+    #[test]
+    fn test_parse_leader_empty_entry_sizes() {
+        for leader in &[
+            "3LE1 0900058 ! 3400",
+            "3LE1 0900058 ! 0404",
+            "3LE1 0900058 ! 3004",
+        ] {
+            let err = parse_leader(leader.as_bytes(), 241).unwrap_err();
+            assert!(matches!(err.kind(), ErrorKind::InvalidLeader));
+        }
+    }
+
+    #[test]
+    fn test_record_name() {
+        let codes = [
+            (10, "DS", RecordName::DS),
+            (20, "DP", RecordName::DP),
+            (30, "DH", RecordName::DH),
+            (40, "DA", RecordName::DA),
+            (60, "CR", RecordName::CR),
+            (70, "ID", RecordName::ID),
+            (80, "IO", RecordName::IO),
+            (90, "IS", RecordName::IS),
+            (100, "FE", RecordName::FE),
+            (110, "VI", RecordName::VI),
+            (120, "VC", RecordName::VC),
+            (130, "VE", RecordName::VE),
+            (140, "VF", RecordName::VF),
+        ];
+        for (code, mnemonic, name) in codes.iter() {
+            assert_eq!(RecordName::try_from(*code).unwrap(), *name);
+            assert_eq!(mnemonic.parse::<RecordName>().unwrap(), *name);
+        }
+        assert_eq!("CD".parse::<RecordName>().unwrap(), RecordName::CD);
+        assert!(RecordName::try_from(50).is_err());
+    }
+
+    #[test]
+    fn test_record_name_unknown() {
+        let err = RecordName::try_from(42).unwrap_err();
+        match err.kind() {
+            ErrorKind::BadRecordName(code) => assert_eq!(code, "42"),
+            kind => panic!("Unexpected error: {}", kind),
+        }
+        assert!("XX".parse::<RecordName>().is_err());
+    }
+
+    #[test]
+    fn test_file_implementation() {
+        assert_eq!(
+            "ASC".parse::<FileImplementation>().unwrap(),
+            FileImplementation::Ascii
+        );
+        assert_eq!(
+            "BIN".parse::<FileImplementation>().unwrap(),
+            FileImplementation::Binary
+        );
+        assert_eq!(
+            "TXT".parse::<FileImplementation>().unwrap(),
+            FileImplementation::Text
+        );
+        let err = "XYZ".parse::<FileImplementation>().unwrap_err();
+        match err.kind() {
+            ErrorKind::BadImplementation(value) => assert_eq!(value, "XYZ"),
+            kind => panic!("Unexpected error: {}", kind),
+        }
+    }
+
+    #[test]
+    fn test_parse_interchange_level() {
+        let leader = parse_leader("3LE1 0900058 ! 3404".as_bytes(), 241).unwrap();
+        assert_eq!(
+            leader.il.to_string().parse::<InterchangeLevel>().unwrap(),
+            InterchangeLevel::Level3
+        );
+        assert_eq!(leader.ai, ' ');
+        assert!(" ".parse::<InterchangeLevel>().is_err());
+    }
+
     #[test]
     fn test_parse_directory() {
         let leader = get_test_leader();
@@ -484,6 +3181,95 @@ mod test {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_parse_directory_misaligned() {
+        let leader = get_test_leader();
+        let directory = "0000019000000010440019CATD120006".as_bytes();
+        let err = parse_directory(directory, &leader).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidLeader));
+    }
+
+    #[test]
+    fn test_data_structure_code_round_trip() {
+        let codes = [
+            DataStructureCode::SDI,
+            DataStructureCode::LS,
+            DataStructureCode::MDS,
+        ];
+        for dsc in codes.iter() {
+            assert_eq!(DataStructureCode::from_str(&dsc.to_string()).unwrap(), *dsc);
+        }
+        assert_eq!(DataStructureCode::MDS.as_str(), "2");
+    }
+
+    #[test]
+    fn test_data_type_code_round_trip() {
+        let codes = [
+            DataTypeCode::CS,
+            DataTypeCode::IP,
+            DataTypeCode::EP,
+            DataTypeCode::BF,
+            DataTypeCode::MDT,
+        ];
+        for dtc in codes.iter() {
+            assert_eq!(DataTypeCode::from_str(&dtc.to_string()).unwrap(), *dtc);
+        }
+        assert_eq!(DataTypeCode::BF.as_str(), "5");
+    }
+
+    #[test]
+    fn test_parse_dir_and_field_area_separator_in_directory() {
+        // The field tag holds a record separator, which must not end the directory
+        let record = b"00039 D     00036   3404AB\x1eC0030000\x1exy\x1e";
+        let mut buf = Vec::new();
+        let (leader, dirs, field_area_idx) =
+            parse_dir_and_field_area(&mut &record[..], &mut buf).unwrap();
+        assert_eq!(leader.ba, 36);
+        assert_eq!(dirs.len(), 1);
+        assert_eq!(dirs[0].id, "AB\x1eC");
+        assert_eq!(dirs[0].length, 3);
+        assert_eq!(&buf[field_area_idx..], b"xy\x1e");
+    }
+
+    #[test]
+    fn test_parse_dir_and_field_area_short_record_length() {
+        for record in [&b"00003"[..], b"00000", b"00023 D     00047   3404\x1e"] {
+            let err = parse_dir_and_field_area(&mut &record[..], &mut Vec::new()).unwrap_err();
+            assert!(matches!(err.kind(), ErrorKind::InvalidLeader));
+        }
+    }
+
+    #[test]
+    fn test_parse_dir_and_field_area_overlapping_fields() {
+        // EFGH starts inside ABCD
+        let record = b"00051 D     00047   3404ABCD0030000EFGH0030001\x1exy\x1e\x1e";
+        let err = parse_dir_and_field_area(&mut &record[..], &mut Vec::new()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::BadDirectoryData));
+        // A length overflowing the position
+        let dirs = vec![DirectoryEntry {
+            id: "ABCD".to_string(),
+            length: usize::MAX,
+            offset: 1,
+        }];
+        assert!(check_directory_regions(&dirs).is_err());
+        // Fields listed out of the order of the field area do not overlap
+        let record = b"00053 D     00047   3404EFGH0030003ABCD0030000\x1exy\x1eab\x1e";
+        let (_, dirs, _) = parse_dir_and_field_area(&mut &record[..], &mut Vec::new()).unwrap();
+        assert_eq!(dirs.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_dir_and_field_area_bad_base_address() {
+        // The base address points into the directory
+        let record = b"00039 D     00034   3404ABCD0030000\x1exy\x1e";
+        let err = parse_dir_and_field_area(&mut &record[..], &mut Vec::new()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::BadDirectoryData));
+        // The base address points past the end of the record
+        let record = b"00039 D     00099   3404ABCD0030000\x1exy\x1e";
+        let err = parse_dir_and_field_area(&mut &record[..], &mut Vec::new()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidLeader));
+    }
+
     #[test]
     fn test_parse_field_controls() {
         let field_controls = "1600;&-A ".as_bytes();
@@ -492,6 +3278,362 @@ mod test {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_decode_latin1() {
+        assert_eq!(decode_latin1(&[0xE5, 0xE4, 0xF6]), "åäö");
+        assert_eq!(
+            parse_to_string(&[0xE5, 0xE4, 0xF6], &TruncEscSeq::LE1).unwrap(),
+            "åäö"
+        );
+    }
+
+    #[test]
+    fn test_parse_to_string_ucs2() {
+        // U+041E, U+041F and U+011F hold the bytes of the separators
+        let objnam = "Онега, Петрозаводск, Ereğli"
+            .encode_utf16()
+            .flat_map(|c| c.to_le_bytes().to_vec())
+            .collect::<Vec<u8>>();
+        let actual = parse_to_string(&objnam, &TruncEscSeq::LE2).unwrap();
+        assert_eq!(actual, "Онега, Петрозаводск, Ereğli");
+    }
+
+    #[test]
+    fn test_parse_to_string_ucs2_odd_length() {
+        assert!(parse_to_string(&[0x04, 0x1c, 0x04], &TruncEscSeq::LE2).is_err());
+    }
+
+    #[test]
+    fn test_parse_file_control_field() {
+        let fcf = "0000;&   \x1f0001DSID0001FRIDFRIDFOID".as_bytes();
+        let expected = FileControlField {
+            dsc: DataStructureCode::SDI,
+            dtc: DataTypeCode::CS,
+            name: "".to_string(),
+            ftp: vec![
+                ("0001".to_string(), "DSID".to_string()),
+                ("0001".to_string(), "FRID".to_string()),
+                ("FRID".to_string(), "FOID".to_string()),
+            ],
+        };
+        let actual = parse_file_control_field(fcf, 4, 9).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_file_control_field_bad_pairs() {
+        let fcf = "0000;&   \x1f0001DSID0001".as_bytes();
+        assert!(parse_file_control_field(fcf, 4, 9).is_err());
+    }
+
+    #[test]
+    fn test_parse_ddfs_truncated_field_area() {
+        let ddr = std::fs::read("tests/CATALOG.031").unwrap();
+        let ddr = &ddr[..262];
+        let leader = parse_leader(&ddr[5..24], ddr.len()).unwrap();
+        let dirs = parse_directory(&ddr[24..72], &leader).unwrap();
+        let field_area = &ddr[73..];
+        assert!(parse_ddfs(field_area, &dirs, 9).is_ok());
+        for len in 0..field_area.len() {
+            assert!(parse_ddfs(&field_area[..len], &dirs, 9).is_err());
+        }
+    }
+
+    #[test]
+    fn test_field_slice_out_of_bounds() {
+        let dir = DirectoryEntry {
+            id: "CATD".to_string(),
+            length: 0,
+            offset: 0,
+        };
+        assert!(field_slice(b"abc", &dir, true, &TruncEscSeq::LE0).is_err());
+        let dir = DirectoryEntry {
+            id: "CATD".to_string(),
+            length: usize::MAX,
+            offset: 2,
+        };
+        assert!(field_slice(b"abc", &dir, true, &TruncEscSeq::LE0).is_err());
+        let dir = DirectoryEntry {
+            id: "CATD".to_string(),
+            length: 2,
+            offset: 1,
+        };
+        assert_eq!(
+            field_slice(b"ab\x1e", &dir, true, &TruncEscSeq::LE0).unwrap(),
+            b"b"
+        );
+    }
+
+    #[test]
+    fn test_field_slice_ucs2_terminator() {
+        let dir = DirectoryEntry {
+            id: "NATF".to_string(),
+            length: 4,
+            offset: 0,
+        };
+        let bytes = [0x1e, 0x04, 0x1e, 0x00];
+        assert_eq!(
+            field_slice(&bytes, &dir, true, &TruncEscSeq::LE2).unwrap(),
+            &bytes[..2]
+        );
+        assert!(field_slice(&[0x00, 0x1e, 0x04, 0x1e], &dir, true, &TruncEscSeq::LE2).is_err());
+    }
+
+    #[test]
+    fn test_field_slice_missing_terminator() {
+        let dir = DirectoryEntry {
+            id: "CATD".to_string(),
+            length: 2,
+            offset: 1,
+        };
+        let err = field_slice(b"abc", &dir, true, &TruncEscSeq::LE0).unwrap_err();
+        match err.kind() {
+            ErrorKind::MissingFieldTerminator(tag) => assert_eq!(tag, "CATD"),
+            kind => panic!("Unexpected error: {}", kind),
+        }
+    }
+
+    #[test]
+    fn test_parse_ddf_date_subfields() {
+        let ddf =
+            "1600;&   Data set identification field\x1fDSNM!UADT!ISDT\x1f(A,A(8),A)".as_bytes();
+        let ddf = parse_ddf(ddf, 9).unwrap();
+        assert_eq!(
+            ddf.foc,
+            vec![
+                ("DSNM".to_string(), ParseData::Variable(ParseType::String)),
+                ("UADT".to_string(), ParseData::Fixed(ParseType::Date, 8)),
+                ("ISDT".to_string(), ParseData::Variable(ParseType::Date)),
+            ]
+        );
+        let formats = ddf
+            .foc
+            .iter()
+            .map(|(_, parser)| parser.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(formats, vec!["A", "A(8)", "A"]);
+    }
+
+    #[test]
+    fn test_parse_to_usize_padded() {
+        assert_eq!(parse_to_usize(b"  241").unwrap(), 241);
+        assert_eq!(parse_to_usize(b"241  ").unwrap(), 241);
+        assert_eq!(parse_to_usize(b"00241").unwrap(), 241);
+        assert_eq!(parse_to_usize(b"241\r\n").unwrap(), 241);
+        assert!(parse_to_usize(b"     ").is_err());
+        assert!(parse_to_usize(b"2 41").is_err());
+    }
+
+    #[test]
+    fn test_is_coordinate_field() {
+        let sg2d = parse_ddf(
+            "2500;&   2-D Coordinate field\x1f*YCOO!XCOO\x1f(2b24)".as_bytes(),
+            9,
+        )
+        .unwrap();
+        assert!(FieldSchema(&sg2d).is_coordinate_field());
+        let sg3d = parse_ddf(
+            "2500;&   3-D Coordinate field\x1fYCOO!XCOO!VE3D\x1f(3b24)".as_bytes(),
+            9,
+        )
+        .unwrap();
+        assert!(FieldSchema(&sg3d).is_coordinate_field());
+        let catd = parse_ddf(
+            "1600;&   Catalogue Directory Field\x1fRCNM!RCID!FILE!SLAT!WLON\x1f(A(2),I(10),A,2R)"
+                .as_bytes(),
+            9,
+        )
+        .unwrap();
+        assert!(!FieldSchema(&catd).is_coordinate_field());
+        // Coordinates that do not repeat are not a coordinate field
+        let single =
+            parse_ddf("1600;&   Single point\x1fYCOO!XCOO\x1f(2b24)".as_bytes(), 9).unwrap();
+        assert!(!FieldSchema(&single).is_coordinate_field());
+    }
+
+    #[test]
+    fn test_parse_repeating_format_controls() {
+        let ddf = "1600;&   2-D coordinate field\x1fYCOO!XCOO\x1f(*(b24,b24))".as_bytes();
+        let ddf = parse_ddf(ddf, 9).unwrap();
+        assert!(ddf.repeating);
+        assert_eq!(
+            FieldSchema(&ddf)
+                .format_controls()
+                .map(|fc| fc.to_string())
+                .collect::<Vec<_>>(),
+            vec!["b24", "b24"]
+        );
+        assert!(parse_repeating_format_controls(b"(*(b24,b24),A)").is_err());
+        assert!(parse_repeating_format_controls(b"(A,*(b24))").is_err());
+
+        // Three coordinate pairs repeat to the end of the field
+        let mut ddr = parse_ddr(&mut std::fs::File::open("tests/CELL.000").unwrap()).unwrap();
+        ddr.data_descriptive_fields.insert("SG2D".to_string(), ddf);
+        let mut record = b"00061 D     00036   3404SG2D0250000\x1e".to_vec();
+        for value in &[1i32, -2, 3, -4, 5, -6] {
+            record.extend_from_slice(&value.to_le_bytes());
+        }
+        record.push(RECORD_SEPARATOR);
+        let (record, _) = parse_dr(
+            &mut &record[..],
+            &ddr,
+            &mut Vec::new(),
+            0,
+            &CatalogOptions::default(),
+            &mut Vec::new(),
+        )
+        .unwrap()
+        .unwrap();
+        let sg2d = record.get_array("SG2D").unwrap();
+        assert_eq!(sg2d.len(), 3);
+        assert_eq!(sg2d[2].get("YCOO"), Some(&Data::Integer(Some(5))));
+        assert_eq!(sg2d[2].get("XCOO"), Some(&Data::Integer(Some(-6))));
+    }
+
+    #[test]
+    fn test_character_set() {
+        for (csi, set) in &[
+            ("   ", CharacterSet::Default),
+            (" ! ", CharacterSet::Default),
+            ("-A ", CharacterSet::Latin1),
+            ("%/A", CharacterSet::Ucs2),
+        ] {
+            assert_eq!(csi.parse::<CharacterSet>().unwrap(), *set);
+        }
+        let err = "%/@".parse::<CharacterSet>().unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::BadCharacterSet(csi) if csi == "%/@"));
+        assert_eq!(
+            get_test_leader().character_set().unwrap(),
+            CharacterSet::Default
+        );
+
+        // The character set of the leader applies to fields with blank field controls
+        let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+        let ddr = parse_ddr(&mut &bytes[..]).unwrap();
+        assert_eq!(ddr.data_descriptive_fields[CATD].fic.tes, TruncEscSeq::LE0);
+        bytes[17..20].copy_from_slice(b"-A ");
+        let ddr = parse_ddr(&mut &bytes[..]).unwrap();
+        assert_eq!(ddr.data_descriptive_fields[CATD].fic.tes, TruncEscSeq::LE1);
+    }
+
+    #[test]
+    fn test_parse_ddf_spare() {
+        let ddf = "1600;&   Test Field\x1fRCNM!RCID\x1f(A(2),X(3),I(5))".as_bytes();
+        let ddf = parse_ddf(ddf, 9).unwrap();
+        assert_eq!(
+            FieldSchema(&ddf).array_descriptors().collect::<Vec<&str>>(),
+            vec!["RCNM", "RCID"]
+        );
+        let formats = ddf
+            .foc
+            .iter()
+            .map(|(_, parser)| parser.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(formats, vec!["A(2)", "X(3)", "I(5)"]);
+
+        let dir = DirectoryEntry {
+            id: "TEST".to_string(),
+            length: 10,
+            offset: 0,
+        };
+        let bytes = b"CD   00042";
+        let field = parse_field(&mut Cursor::new(&bytes[..]), &dir, &ddf, 0, None).unwrap();
+        assert_eq!(field.len(), 2);
+        assert_eq!(field["RCNM"], Data::String("CD".to_string()));
+        assert_eq!(field["RCID"], Data::Integer(Some(42)));
+    }
+
+    #[test]
+    fn test_parse_ddf_terminator() {
+        let ddf = "1600;&   Test Field\x1fRCNM!RCID!COMT\x1f(A(2),I(5),@,A)".as_bytes();
+        let ddf = parse_ddf(ddf, 9).unwrap();
+        assert_eq!(
+            FieldSchema(&ddf).array_descriptors().collect::<Vec<&str>>(),
+            vec!["RCNM", "RCID", "COMT"]
+        );
+        let formats = ddf
+            .foc
+            .iter()
+            .map(|(_, parser)| parser.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(formats, vec!["A(2)", "I(5)", "@", "A"]);
+
+        let dir = DirectoryEntry {
+            id: "TEST".to_string(),
+            length: 7,
+            offset: 0,
+        };
+        let bytes = b"CD00042";
+        let field = parse_field(&mut Cursor::new(&bytes[..]), &dir, &ddf, 0, None).unwrap();
+        assert_eq!(field.len(), 2);
+        assert_eq!(field["RCNM"], Data::String("CD".to_string()));
+        assert_eq!(field["RCID"], Data::Integer(Some(42)));
+    }
+
+    #[test]
+    fn test_parse_field_integer_overflow() {
+        let ddf = "1600;&   Test Field\x1fRCNM!RCID\x1f(A(2),I(25))".as_bytes();
+        let ddf = parse_ddf(ddf, 9).unwrap();
+        let dir = DirectoryEntry {
+            id: "TEST".to_string(),
+            length: 27,
+            offset: 0,
+        };
+        let bytes = b"CD9999999999999999999999999";
+        let err = parse_field(&mut Cursor::new(&bytes[..]), &dir, &ddf, 0, None).unwrap_err();
+        match err.kind() {
+            ErrorKind::NumericOverflow { field, width } => {
+                assert_eq!(field, "TEST.RCID");
+                assert_eq!(*width, 25);
+            }
+            kind => panic!("unexpected error {:?}", kind),
+        }
+        // The widest integer that fits
+        let ddf = "1600;&   Test Field\x1fRCNM!RCID\x1f(A(2),I(20))".as_bytes();
+        let ddf = parse_ddf(ddf, 9).unwrap();
+        let bytes = b"CD-9223372036854775808";
+        let field = parse_field(&mut Cursor::new(&bytes[..]), &dir, &ddf, 0, None).unwrap();
+        assert_eq!(field["RCID"], Data::Integer(Some(i64::MIN)));
+        let bytes = b"CD99999999999999999999";
+        let err = parse_field(&mut Cursor::new(&bytes[..]), &dir, &ddf, 0, None).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::NumericOverflow { width: 20, .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_ddf_short_field_controls() {
+        let ddf = "1600;&\x1fRCNM\x1f(A(2))".as_bytes();
+        let err = parse_ddf(ddf, 9).unwrap_err();
+        match err.kind() {
+            ErrorKind::InvalidDDF(msg) => assert_eq!(msg, "field controls of 6 bytes"),
+            kind => panic!("Unexpected error: {}", kind),
+        }
+        let err = parse_ddf("1600;&   Test Field".as_bytes(), 9).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidDDF(_)));
+        let err = parse_ddf("1600;&   Test Field\x1fRCNM".as_bytes(), 9).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidDDF(_)));
+    }
+
+    #[test]
+    fn test_parse_ddf_descriptor_format_mismatch() {
+        let ddf = "1600;&   Test Field\x1fRCNM!RCID!FILE\x1f(A(2),I(10))".as_bytes();
+        let err = parse_ddf(ddf, 9).unwrap_err();
+        match err.kind() {
+            ErrorKind::DescriptorFormatMismatch {
+                field,
+                descriptors,
+                formats,
+            } => {
+                assert_eq!(field, "Test Field");
+                assert_eq!(*descriptors, 3);
+                assert_eq!(*formats, 2);
+            }
+            kind => panic!("Unexpected error: {}", kind),
+        }
+    }
+
     #[test]
     fn test_parse_array_descriptor() {
         let array_descriptor =
@@ -510,6 +3652,53 @@ mod test {
         assert!(parse_format_controls(array_descriptor).is_err())
     }
 
+    #[test]
+    fn test_parse_binary_format_controls() {
+        let format_controls = "(b11,b24)".as_bytes();
+        let expected = vec![
+            ParseData::Binary {
+                signed: false,
+                width: 1,
+            },
+            ParseData::Binary {
+                signed: true,
+                width: 4,
+            },
+        ];
+        let actual = parse_format_controls(format_controls).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_nested_format_controls() {
+        let format_controls = "(2(A(2),I(5)))".as_bytes();
+        let expected = vec![
+            ParseData::Fixed(ParseType::String, 2),
+            ParseData::Fixed(ParseType::Integer, 5),
+            ParseData::Fixed(ParseType::String, 2),
+            ParseData::Fixed(ParseType::Integer, 5),
+        ];
+        let actual = parse_format_controls(format_controls).unwrap();
+        assert_eq!(actual, expected);
+
+        let format_controls = "(A(2),(3I(5)),R)".as_bytes();
+        let expected = vec![
+            ParseData::Fixed(ParseType::String, 2),
+            ParseData::Fixed(ParseType::Integer, 5),
+            ParseData::Fixed(ParseType::Integer, 5),
+            ParseData::Fixed(ParseType::Integer, 5),
+            ParseData::Variable(ParseType::Float),
+        ];
+        let actual = parse_format_controls(format_controls).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_unbalanced_format_controls() {
+        assert!(parse_format_controls("(2(A(2),I(5))".as_bytes()).is_err());
+        assert!(parse_format_controls("(A(2)),I(5))".as_bytes()).is_err());
+    }
+
     #[test]
     fn test_parse_format_controls() {
         let format_controls = "(A(2),2I(10),2R)".as_bytes();
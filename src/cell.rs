@@ -0,0 +1,552 @@
+//! The cell.rs provides the functionality to parse an ENC base cell, i.e. the `*.000` data set
+//! files referenced by the catalog. A cell is an ISO 8211 file just like the catalog, so it is read
+//! with the same DDR and Data Record machinery, but its records hold `DSID`, `DSSI`, feature
+//! (`FRID`) and vector (`VRID`) fields instead of `CATD`.
+use crate::catalog::{
+    apply_comf, parse_ddr, parse_dr, to_u32, CatalogOptions, Data, Field, Record, RecordName,
+    Result, Warning, DDR,
+};
+use crate::error::ErrorKind;
+use failure::ResultExt;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::Read;
+
+#[derive(Debug)]
+pub struct Cell<R: Read> {
+    ddr: DDR,     // Data Descriptive Record
+    rdr: R,       // reader to ask for Data Records
+    buf: Vec<u8>, // holds the current Data Record
+    pos: u64,     // bytes read from rdr, counted from the start of the DDR
+    warnings: Vec<Warning>,
+}
+
+impl<R: Read> Cell<R> {
+    pub fn new(mut rdr: R) -> Result<Cell<R>> {
+        let ddr = parse_ddr(&mut rdr).context(ErrorKind::CouldNotParseCell)?;
+        let pos = ddr.length() as u64;
+        Ok(Cell {
+            ddr,
+            rdr,
+            buf: Vec::new(),
+            pos,
+            warnings: Vec::new(),
+        })
+    }
+
+    /// The field tag pairs from the File Control Field as (parent, child). Together they describe
+    /// the tree of fields below the record identifier field `0001`.
+    pub fn field_tag_pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.ddr.field_tag_pairs()
+    }
+
+    /// The nonfatal deviations found in the records read so far, see `Catalog::warnings`.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Iterates over the Data Records of the cell, in the order they are stored in the file.
+    pub fn records(&mut self) -> impl Iterator<Item = Result<Record>> + '_ {
+        std::iter::from_fn(move || {
+            parse_dr(
+                &mut self.rdr,
+                &self.ddr,
+                &mut self.buf,
+                self.pos,
+                &CatalogOptions::default(),
+                &mut self.warnings,
+            )
+            .map(|dr| {
+                dr.map(|(record, length)| {
+                    self.pos += length as u64;
+                    record
+                })
+            })
+            .transpose()
+        })
+    }
+}
+
+const DSPM: &str = "DSPM";
+
+/// The horizontal datum `HDAT` of a data set. ENCs always use WGS 84.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum HorizontalDatum {
+    Wgs84,
+    Other(i64), // One of the other codes of the S-57 attribute HORDAT
+}
+
+impl From<i64> for HorizontalDatum {
+    fn from(code: i64) -> HorizontalDatum {
+        match code {
+            2 => HorizontalDatum::Wgs84,
+            _ => HorizontalDatum::Other(code),
+        }
+    }
+}
+
+/// A vertical or sounding datum, `VDAT` or `SDAT`, with the codes of the S-57 attribute VERDAT.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum VerticalDatum {
+    MeanLowWaterSprings,               // 1
+    MeanLowerLowWaterSprings,          // 2
+    MeanSeaLevel,                      // 3
+    LowestLowWater,                    // 4
+    MeanLowWater,                      // 5
+    LowestLowWaterSprings,             // 6
+    ApproximateMeanLowWaterSprings,    // 7
+    IndianSpringLowWater,              // 8
+    LowWaterSprings,                   // 9
+    ApproximateLowestAstronomicalTide, // 10
+    NearlyLowestLowWater,              // 11
+    MeanLowerLowWater,                 // 12
+    LowWater,                          // 13
+    ApproximateMeanLowWater,           // 14
+    ApproximateMeanLowerLowWater,      // 15
+    MeanHighWater,                     // 16
+    MeanHighWaterSprings,              // 17
+    HighWater,                         // 18
+    ApproximateMeanSeaLevel,           // 19
+    HighWaterSprings,                  // 20
+    MeanHigherHighWater,               // 21
+    EquinoctialSpringLowWater,         // 22
+    LowestAstronomicalTide,            // 23
+    LocalDatum,                        // 24
+    InternationalGreatLakesDatum1985,  // 25
+    MeanWaterLevel,                    // 26
+    LowerLowWaterLargeTide,            // 27
+    HigherHighWaterLargeTide,          // 28
+    NearlyHighestHighWater,            // 29
+    HighestAstronomicalTide,           // 30
+    Other(i64),                        // A code not in the list above
+}
+
+impl From<i64> for VerticalDatum {
+    fn from(code: i64) -> VerticalDatum {
+        use VerticalDatum::*;
+        match code {
+            1 => MeanLowWaterSprings,
+            2 => MeanLowerLowWaterSprings,
+            3 => MeanSeaLevel,
+            4 => LowestLowWater,
+            5 => MeanLowWater,
+            6 => LowestLowWaterSprings,
+            7 => ApproximateMeanLowWaterSprings,
+            8 => IndianSpringLowWater,
+            9 => LowWaterSprings,
+            10 => ApproximateLowestAstronomicalTide,
+            11 => NearlyLowestLowWater,
+            12 => MeanLowerLowWater,
+            13 => LowWater,
+            14 => ApproximateMeanLowWater,
+            15 => ApproximateMeanLowerLowWater,
+            16 => MeanHighWater,
+            17 => MeanHighWaterSprings,
+            18 => HighWater,
+            19 => ApproximateMeanSeaLevel,
+            20 => HighWaterSprings,
+            21 => MeanHigherHighWater,
+            22 => EquinoctialSpringLowWater,
+            23 => LowestAstronomicalTide,
+            24 => LocalDatum,
+            25 => InternationalGreatLakesDatum1985,
+            26 => MeanWaterLevel,
+            27 => LowerLowWaterLargeTide,
+            28 => HigherHighWaterLargeTide,
+            29 => NearlyHighestHighWater,
+            30 => HighestAstronomicalTide,
+            _ => Other(code),
+        }
+    }
+}
+
+/// The data set parameters from the `DSPM` field of the data set parameter record.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct DatasetParameters {
+    /// The horizontal geodetic datum `HDAT`.
+    pub horizontal_datum: HorizontalDatum,
+    /// The vertical datum `VDAT`.
+    pub vertical_datum: VerticalDatum,
+    /// The sounding datum `SDAT`.
+    pub sounding_datum: VerticalDatum,
+    /// The compilation scale `CSCL`, the denominator of the scale.
+    pub compilation_scale: u32,
+    /// The coordinate multiplication factor `COMF`, see `Record::comf`.
+    pub comf: u32,
+    /// The 3-D (sounding) multiplication factor `SOMF`, see `Record::somf`.
+    pub somf: u32,
+}
+
+fn dspm_value(record: &Record, subfield: &str) -> Result<i64> {
+    record.get_i64(DSPM, subfield).ok_or_else(|| {
+        ErrorKind::MissingSubfield {
+            field: DSPM.to_string(),
+            subfield: subfield.to_string(),
+        }
+        .into()
+    })
+}
+
+/// Reads the `DSPM` field of the data set parameter record.
+pub fn parse_dspm(record: &Record) -> Result<DatasetParameters> {
+    Ok(DatasetParameters {
+        horizontal_datum: dspm_value(record, "HDAT")?.into(),
+        vertical_datum: dspm_value(record, "VDAT")?.into(),
+        sounding_datum: dspm_value(record, "SDAT")?.into(),
+        compilation_scale: to_u32("CSCL", dspm_value(record, "CSCL")?)?,
+        comf: to_u32("COMF", dspm_value(record, "COMF")?)?,
+        somf: to_u32("SOMF", dspm_value(record, "SOMF")?)?,
+    })
+}
+
+/// A foreign pointer of a feature record (`FSPT`) to a vector record, or of a vector record
+/// (`VRPT`) to another vector record. The pointed record is identified by its record name and
+/// record id. The orientation, usage indicator, topology indicator and masking indicator are
+/// None when the field does not have them, e.g. `FSPT` has no `TOPI`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RecordPointer {
+    /// The RCNM of the NAME subfield.
+    pub name: RecordName,
+    /// The RCID of the NAME subfield.
+    pub id: i64,
+    /// The orientation `ORNT`.
+    pub ornt: Option<i64>,
+    /// The usage indicator `USAG`.
+    pub usag: Option<i64>,
+    /// The topology indicator `TOPI`.
+    pub topi: Option<i64>,
+    /// The masking indicator `MASK`.
+    pub mask: Option<i64>,
+}
+
+// Decodes the 5 byte NAME subfield of a foreign pointer, the RCNM as an unsigned byte followed by
+// the RCID as an unsigned little-endian 4 byte integer
+fn parse_pointer_name(name: &[u8]) -> Result<(RecordName, i64)> {
+    if name.len() != 5 {
+        return Err(ErrorKind::BadRecordPointer(format!(
+            "NAME of {} bytes, expected 5",
+            name.len()
+        ))
+        .into());
+    }
+    let rcnm = i64::from(name[0]);
+    let name_code = RecordName::try_from(rcnm)?;
+    let rcid = u32::from_le_bytes([name[1], name[2], name[3], name[4]]);
+    Ok((name_code, i64::from(rcid)))
+}
+
+// The repetitions of a repeating field, a field that does not repeat is a single repetition
+fn repetitions(field: &Field) -> &[Field] {
+    match field.values().find_map(|data| match data {
+        Data::Array(elements) => Some(elements.as_slice()),
+        _ => None,
+    }) {
+        Some(elements) => elements,
+        None => std::slice::from_ref(field),
+    }
+}
+
+/// Reads the foreign pointers of an `FSPT` or `VRPT` field, one per repetition of the field.
+pub fn parse_pointers(field: &Field) -> Result<Vec<RecordPointer>> {
+    repetitions(field)
+        .iter()
+        .map(|element| {
+            let (name, id) = match element.get("NAME") {
+                Some(Data::Binary(bytes)) => parse_pointer_name(bytes)?,
+                _ => return Err(ErrorKind::BadRecordPointer("missing NAME".to_string()).into()),
+            };
+            let indicator = |subfield: &str| match element.get(subfield) {
+                Some(Data::Integer(value)) => *value,
+                _ => None,
+            };
+            Ok(RecordPointer {
+                name,
+                id,
+                ornt: indicator("ORNT"),
+                usag: indicator("USAG"),
+                topi: indicator("TOPI"),
+                mask: indicator("MASK"),
+            })
+        })
+        .collect()
+}
+
+/// Reads the attributes of an `ATTF` or `NATF` field, mapping the attribute label (`ATTL`) of
+/// every repetition to its value (`ATVL`).
+pub fn parse_attributes(field: &Field) -> Result<HashMap<u16, String>> {
+    repetitions(field)
+        .iter()
+        .map(|element| {
+            let label = match element.get("ATTL") {
+                Some(Data::Integer(Some(label))) => u16::try_from(*label).ok(),
+                Some(Data::String(label)) => label.trim().parse().ok(),
+                _ => None,
+            }
+            .ok_or_else(|| ErrorKind::BadAttribute("missing or invalid ATTL".to_string()))?;
+            let value = match element.get("ATVL") {
+                Some(Data::String(value)) => value.clone(),
+                Some(Data::Integer(None)) | Some(Data::Float(None)) => String::new(),
+                Some(Data::Integer(Some(value))) => value.to_string(),
+                Some(Data::Float(Some(value))) => value.to_string(),
+                _ => {
+                    return Err(
+                        ErrorKind::BadAttribute(format!("missing ATVL of {}", label)).into(),
+                    )
+                }
+            };
+            Ok((label, value))
+        })
+        .collect()
+}
+
+// Reads an integer coordinate subfield of one repetition of SG2D or SG3D
+fn coordinate_value(element: &Field, subfield: &str) -> Result<i64> {
+    element
+        .get(subfield)
+        .and_then(Data::as_i64)
+        .ok_or_else(|| ErrorKind::BadCoordinate(subfield.to_string()).into())
+}
+
+/// Reads the coordinates of a 2-D (`SG2D`) or 3-D (`SG3D`) coordinate field as [longitude,
+/// latitude, depth]. Longitude and latitude are scaled by the COMF and the sounding `VE3D` by the
+/// SOMF of the data set, see `Record::comf` and `Record::somf`. The depth of 2-D coordinates is 0.
+pub fn parse_coordinates(field: &Field, comf: u32, somf: u32) -> Result<Vec<[f64; 3]>> {
+    let elements = field
+        .values()
+        .find_map(|data| match data {
+            Data::Array(elements) => Some(elements),
+            _ => None,
+        })
+        .ok_or(ErrorKind::CoordinatesNotRepeating)?;
+    elements
+        .iter()
+        .map(|element| {
+            let y = coordinate_value(element, "YCOO")?;
+            let x = coordinate_value(element, "XCOO")?;
+            let depth = match element.get("VE3D") {
+                Some(_) => apply_comf(coordinate_value(element, "VE3D")?, somf),
+                None => 0.0,
+            };
+            Ok([apply_comf(x, comf), apply_comf(y, comf), depth])
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::catalog::TruncEscSeq;
+    use crate::data_parser::ParseData;
+    use std::io::Cursor;
+
+    // Parses repetitions of signed 4 byte subfields like the binary implementation does
+    fn get_coordinate_field(labels: &[&str], bytes: &[u8]) -> Field {
+        let parser = ParseData::Binary {
+            signed: true,
+            width: 4,
+        };
+        let mut cur = Cursor::new(bytes);
+        let mut elements = Vec::new();
+        while (cur.position() as usize) < bytes.len() {
+            let element = labels
+                .iter()
+                .map(|label| {
+                    let data = parser.parse(&mut cur, &TruncEscSeq::LE0).unwrap();
+                    (label.to_string(), data)
+                })
+                .collect::<Field>();
+            elements.push(element);
+        }
+        let mut field = Field::new();
+        field.insert(labels.join("!"), Data::Array(elements));
+        field
+    }
+
+    fn get_dspm_record(subfields: &[(&str, i64)]) -> Record {
+        let dspm = subfields
+            .iter()
+            .map(|(name, value)| (name.to_string(), Data::Integer(Some(*value))))
+            .collect::<Field>();
+        let mut record = Record::default();
+        record.insert(DSPM.to_string(), dspm);
+        record
+    }
+
+    #[test]
+    fn test_parse_dspm() {
+        let record = get_dspm_record(&[
+            ("RCNM", 20),
+            ("RCID", 1),
+            ("HDAT", 2),
+            ("VDAT", 17),
+            ("SDAT", 23),
+            ("CSCL", 52000),
+            ("COMF", 10_000_000),
+            ("SOMF", 10),
+        ]);
+        assert_eq!(
+            parse_dspm(&record).unwrap(),
+            DatasetParameters {
+                horizontal_datum: HorizontalDatum::Wgs84,
+                vertical_datum: VerticalDatum::MeanHighWaterSprings,
+                sounding_datum: VerticalDatum::LowestAstronomicalTide,
+                compilation_scale: 52000,
+                comf: 10_000_000,
+                somf: 10,
+            }
+        );
+        assert_eq!(record.comf().unwrap(), Some(10_000_000));
+    }
+
+    #[test]
+    fn test_parse_dspm_missing_subfield() {
+        let record = get_dspm_record(&[("HDAT", 7), ("VDAT", 99), ("SDAT", 3)]);
+        let err = parse_dspm(&record).unwrap_err();
+        match err.kind() {
+            ErrorKind::MissingSubfield { field, subfield } => {
+                assert_eq!((field.as_str(), subfield.as_str()), ("DSPM", "CSCL"))
+            }
+            kind => panic!("Unexpected error: {}", kind),
+        }
+        assert_eq!(HorizontalDatum::from(7), HorizontalDatum::Other(7));
+        assert_eq!(VerticalDatum::from(99), VerticalDatum::Other(99));
+    }
+
+    #[test]
+    fn test_parse_dspm_out_of_range() {
+        let record = get_dspm_record(&[
+            ("HDAT", 2),
+            ("VDAT", 16),
+            ("SDAT", 23),
+            ("CSCL", 52000),
+            ("COMF", -1),
+            ("SOMF", 10),
+        ]);
+        let err = parse_dspm(&record).unwrap_err();
+        match err.kind() {
+            ErrorKind::BadAttribute(message) => assert_eq!(message, "COMF out of range: -1"),
+            kind => panic!("Unexpected error: {}", kind),
+        }
+        assert!(record.comf().is_err());
+        assert_eq!(record.somf().unwrap(), Some(10));
+        assert_eq!(Record::default().comf().unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_attributes() {
+        // ATTF with the OBJNAM (116) and the DRVAL1 (87) of a feature
+        let attribute = |label: i64, value: &str| {
+            let mut element = Field::new();
+            element.insert("ATTL".to_string(), Data::Integer(Some(label)));
+            element.insert("ATVL".to_string(), Data::String(value.to_string()));
+            element
+        };
+        let mut attf = Field::new();
+        attf.insert(
+            "ATTL!ATVL".to_string(),
+            Data::Array(vec![attribute(116, "Sandvika"), attribute(87, "5.5")]),
+        );
+        let attributes = parse_attributes(&attf).unwrap();
+        assert_eq!(attributes.len(), 2);
+        assert_eq!(attributes[&116], "Sandvika");
+        assert_eq!(attributes[&87], "5.5");
+
+        attf.insert(
+            "ATTL!ATVL".to_string(),
+            Data::Array(vec![attribute(-1, "")]),
+        );
+        assert!(parse_attributes(&attf).is_err());
+    }
+
+    #[test]
+    fn test_parse_pointers() {
+        // VRPT with two pointers to the connected nodes 1234 and 70000 (RCNM 120)
+        let mut first = Field::new();
+        first.insert(
+            "NAME".to_string(),
+            Data::Binary(vec![120, 0xd2, 0x04, 0x00, 0x00]),
+        );
+        first.insert("ORNT".to_string(), Data::Integer(Some(255)));
+        first.insert("USAG".to_string(), Data::Integer(Some(255)));
+        first.insert("TOPI".to_string(), Data::Integer(Some(1)));
+        first.insert("MASK".to_string(), Data::Integer(Some(255)));
+        let mut second = first.clone();
+        second.insert(
+            "NAME".to_string(),
+            Data::Binary(vec![120, 0x70, 0x11, 0x01, 0x00]),
+        );
+        second.insert("TOPI".to_string(), Data::Integer(Some(2)));
+        let mut vrpt = Field::new();
+        vrpt.insert(
+            "NAME!ORNT!USAG!TOPI!MASK".to_string(),
+            Data::Array(vec![first, second]),
+        );
+        let pointers = parse_pointers(&vrpt).unwrap();
+        assert_eq!(pointers.len(), 2);
+        assert_eq!(
+            pointers[0],
+            RecordPointer {
+                name: RecordName::VC,
+                id: 1234,
+                ornt: Some(255),
+                usag: Some(255),
+                topi: Some(1),
+                mask: Some(255),
+            }
+        );
+        assert_eq!(pointers[1].id, 70000);
+        assert_eq!(pointers[1].topi, Some(2));
+    }
+
+    #[test]
+    fn test_parse_pointers_bad_name() {
+        let mut fspt = Field::new();
+        fspt.insert("NAME".to_string(), Data::Binary(vec![110, 1, 0, 0]));
+        let err = parse_pointers(&fspt).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::BadRecordPointer(_)));
+        fspt.insert("NAME".to_string(), Data::Binary(vec![99, 1, 0, 0, 0]));
+        let err = parse_pointers(&fspt).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::BadRecordName(_)));
+        fspt.insert("NAME".to_string(), Data::Binary(vec![130, 1, 0, 0, 0]));
+        let pointers = parse_pointers(&fspt).unwrap();
+        assert_eq!(pointers[0].name, RecordName::VE);
+        assert_eq!(pointers[0].topi, None);
+    }
+
+    #[test]
+    fn test_parse_coordinates_2d() {
+        // YCOO 593456789, XCOO -102500000
+        let bytes = [0x95, 0x6e, 0x5f, 0x23, 0x60, 0xf9, 0xe3, 0xf9];
+        let field = get_coordinate_field(&["YCOO", "XCOO"], &bytes);
+        let coordinates = parse_coordinates(&field, 10_000_000, 10).unwrap();
+        assert_eq!(coordinates, vec![[-10.25, 59.3456789, 0.0]]);
+    }
+
+    #[test]
+    fn test_parse_coordinates_3d() {
+        // Two soundings: (YCOO 600000000, XCOO 50000000, VE3D 125) and (-1, -2, -3)
+        let mut bytes = Vec::new();
+        for value in &[600_000_000i32, 50_000_000, 125, -1, -2, -3] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        let field = get_coordinate_field(&["YCOO", "XCOO", "VE3D"], &bytes);
+        let coordinates = parse_coordinates(&field, 10_000_000, 10).unwrap();
+        assert_eq!(
+            coordinates,
+            vec![[5.0, 60.0, 12.5], [-0.0000002, -0.0000001, -0.3]]
+        );
+    }
+
+    #[test]
+    fn test_parse_coordinates_bad_field() {
+        let mut field = Field::new();
+        field.insert("YCOO".to_string(), Data::Integer(Some(1)));
+        assert!(parse_coordinates(&field, 1, 1).is_err());
+        let field = get_coordinate_field(&["YCOO", "ZCOO"], &[0; 8]);
+        let err = parse_coordinates(&field, 1, 1).unwrap_err();
+        match err.kind() {
+            ErrorKind::BadCoordinate(subfield) => assert_eq!(subfield, "XCOO"),
+            kind => panic!("Unexpected error: {}", kind),
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use crate::catalog::{Result, UNIT_SEPARATOR};
+use crate::catalog::{
+    parse_to_string, Field, Result, TruncEscSeq, RECORD_SEPARATOR, UNIT_SEPARATOR,
+};
 use crate::error::ErrorKind;
 use failure::ResultExt;
 use lazy_static::lazy_static;
@@ -6,16 +8,33 @@ use regex::Regex;
 use std::fmt::{Display, Formatter};
 use std::io::prelude::*;
 
+#[cfg(feature = "chrono")]
+const DATE_FORMAT: &str = "%Y%m%d";
+
 lazy_static! {
-    // Maybe a number folowed by A,I,R followed by maybe a parenthesied number
+    // Maybe a number folowed by A,I,R,B followed by maybe a parenthesied number
     // See tests
-    static ref FIELD_REGEX: Regex = Regex::new(r"^(\d+)?([AIR])(\(\d*\))?").unwrap();
+    static ref FIELD_REGEX: Regex = Regex::new(r"^(\d+)?([AIRB])(\(\d*\))?").unwrap();
+    // Maybe a number followed by X, a spare of the parenthesied width in bytes
+    static ref SPARE_REGEX: Regex = Regex::new(r"^(\d+)?X\((\d+)\)$").unwrap();
+    // Maybe a number followed by b, the signedness (1 unsigned, 2 signed) and the width in bytes
+    static ref BINARY_REGEX: Regex = Regex::new(r"^(\d+)?b([12])([124])$").unwrap();
 }
 
+// The widest fixed width integer that fits an i64, i.e. the sign and 19 digits
+const MAX_INTEGER_WIDTH: usize = 20;
+
 #[derive(Debug, PartialEq, Clone)]
 pub(crate) enum ParseData {
     Fixed(ParseType, usize),
     Variable(ParseType),
+    // Little-endian binary integer with the width in bytes
+    Binary { signed: bool, width: usize },
+    // Fill bytes of the given width without an array descriptor, skipped when parsing
+    Spare(usize),
+    // The end of the field, written @, the subfields after it are not read. It has no array
+    // descriptor and no width.
+    Terminator,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -23,16 +42,101 @@ pub(crate) enum ParseType {
     Integer,
     String,
     Float,
+    Binary, // Raw bytes, the width of a B(n) format control is given in bits
+    Date,   // A string of the form YYYYMMDD, not a format control of its own
 }
 
-// Variable length fields are optional (can be empty), therefore Options
-#[derive(Debug, PartialEq)]
+/// The value of a subfield. A variable length subfield can be present but empty, i.e. directly
+/// terminated by the unit separator. An empty string subfield is an empty `Data::String`, an empty
+/// numeric subfield is `Data::Integer(None)` or `Data::Float(None)` and an empty binary subfield is
+/// an empty `Data::Binary`.
+///
+/// So the `Option` of a numeric subfield is only None for an empty subfield, `Data::as_i64` and
+/// `Data::as_f64` give the number without matching both layers.
+///
+/// Floats are compared bitwise, so a NaN equals itself and 0.0 does not equal -0.0. Two subfields
+/// are equal when they were parsed from the same digits.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum Data {
+    /// An integer, None if the subfield is empty.
     Integer(Option<i64>),
     String(String),
+    /// A float, None if the subfield is empty.
     Float(Option<f64>),
+    // The repetitions of a repeating field, e.g. the coordinates of SG2D
+    Array(Vec<Field>),
+    Binary(Vec<u8>),
+    #[cfg(feature = "chrono")]
+    Date(chrono::NaiveDate),
+}
+
+impl Data {
+    /// The number of an integer subfield, None for an empty subfield and all other subfields.
+    pub fn as_i64(&self) -> Option<i64> {
+        match &self {
+            Data::Integer(i) => *i,
+            _ => None,
+        }
+    }
+
+    /// The number of a float subfield, None for an empty subfield and all other subfields.
+    pub fn as_f64(&self) -> Option<f64> {
+        match &self {
+            Data::Float(f) => *f,
+            _ => None,
+        }
+    }
+
+    /// The text of a string subfield, None for all other subfields. An empty subfield is an empty
+    /// string.
+    pub fn as_str(&self) -> Option<&str> {
+        match &self {
+            Data::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Whether the subfield is an empty numeric subfield, i.e. `Integer(None)` or `Float(None)`.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Data::Integer(None) | Data::Float(None))
+    }
+
+    /// The raw bytes of a binary subfield, None for all other subfields.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match &self {
+            Data::Binary(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// The date of a date subfield like ISDT, None for all other subfields and malformed dates.
+    #[cfg(feature = "chrono")]
+    pub fn as_date(&self) -> Option<chrono::NaiveDate> {
+        match &self {
+            Data::Date(date) => Some(*date),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for Data {
+    fn eq(&self, other: &Data) -> bool {
+        match (self, other) {
+            (Data::Integer(a), Data::Integer(b)) => a == b,
+            (Data::String(a), Data::String(b)) => a == b,
+            (Data::Float(a), Data::Float(b)) => a.map(f64::to_bits) == b.map(f64::to_bits),
+            (Data::Array(a), Data::Array(b)) => a == b,
+            (Data::Binary(a), Data::Binary(b)) => a == b,
+            #[cfg(feature = "chrono")]
+            (Data::Date(a), Data::Date(b)) => a == b,
+            _ => false,
+        }
+    }
 }
 
+impl Eq for Data {}
+
 impl Display for Data {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         match &self {
@@ -45,12 +149,142 @@ impl Display for Data {
                 Some(x) => Display::fmt(x, f),
                 None => Display::fmt("", f),
             },
+            // Displays as [{XCOO: 1, YCOO: 2}, ...] with the subfields sorted by name
+            Data::Array(elements) => {
+                let elements = elements
+                    .iter()
+                    .map(|field| {
+                        let mut subfields = field
+                            .iter()
+                            .map(|(name, data)| format!("{}: {}", name, data))
+                            .collect::<Vec<String>>();
+                        subfields.sort();
+                        format!("{{{}}}", subfields.join(", "))
+                    })
+                    .collect::<Vec<String>>();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            // Displays as hexadecimal
+            Data::Binary(bytes) => bytes.iter().try_for_each(|b| write!(f, "{:02X}", b)),
+            // Displays as stored, YYYYMMDD
+            #[cfg(feature = "chrono")]
+            Data::Date(date) => Display::fmt(&date.format(DATE_FORMAT), f),
+        }
+    }
+}
+
+// Serializes to the natural type, with empty subfields as null
+#[cfg(feature = "serde")]
+impl serde::Serialize for Data {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        match &self {
+            Data::Integer(Some(x)) => serializer.serialize_i64(*x),
+            Data::String(val) => serializer.serialize_str(val),
+            Data::Float(Some(x)) => serializer.serialize_f64(*x),
+            Data::Integer(None) | Data::Float(None) => serializer.serialize_none(),
+            Data::Array(elements) => serializer.collect_seq(elements),
+            Data::Binary(bytes) => serializer.serialize_bytes(bytes),
+            #[cfg(feature = "chrono")]
+            Data::Date(date) => serializer.collect_str(&date.format(DATE_FORMAT)),
+        }
+    }
+}
+
+// Converts to the natural JSON type like the serialization, with empty subfields as null, binary
+// subfields as an array of bytes and the repetitions of a field as an array of objects
+#[cfg(feature = "serde_json")]
+impl From<&Data> for serde_json::Value {
+    fn from(data: &Data) -> serde_json::Value {
+        use serde_json::Value;
+        match data {
+            Data::Integer(Some(x)) => Value::from(*x),
+            Data::String(val) => Value::from(val.as_str()),
+            // NaN and infinity have no JSON representation either
+            Data::Float(Some(x)) => serde_json::Number::from_f64(*x)
+                .map(Value::Number)
+                .unwrap_or(Value::Null),
+            Data::Integer(None) | Data::Float(None) => Value::Null,
+            Data::Array(elements) => Value::Array(
+                elements
+                    .iter()
+                    .map(|field| {
+                        Value::Object(
+                            field
+                                .iter()
+                                .map(|(name, data)| (name.clone(), Value::from(data)))
+                                .collect(),
+                        )
+                    })
+                    .collect(),
+            ),
+            Data::Binary(bytes) => Value::from(bytes.as_slice()),
+            #[cfg(feature = "chrono")]
+            Data::Date(date) => Value::from(date.format(DATE_FORMAT).to_string()),
+        }
+    }
+}
+
+impl Display for ParseType {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match &self {
+            ParseType::Integer => Display::fmt("I", f),
+            ParseType::String => Display::fmt("A", f),
+            ParseType::Float => Display::fmt("R", f),
+            ParseType::Binary => Display::fmt("B", f),
+            ParseType::Date => Display::fmt("A", f),
+        }
+    }
+}
+
+// The optional repeat count in front of a format control, 1 if it is missing. A count that does not
+// fit a usize makes the format control unparsable.
+fn repeat_count(s: &str, count: Option<regex::Match<'_>>) -> Result<usize> {
+    match count {
+        Some(count) => count
+            .as_str()
+            .parse()
+            .map_err(|_| ErrorKind::UnParsableFormatControl(String::from(s)).into()),
+        None => Ok(1),
+    }
+}
+
+// Displays the format control as it is written in the DDR, e.g. A(2) or R
+impl Display for ParseData {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match &self {
+            ParseData::Fixed(ParseType::Binary, size) => write!(f, "B({})", size * 8),
+            ParseData::Fixed(t, size) => write!(f, "{}({})", t, size),
+            ParseData::Variable(t) => Display::fmt(t, f),
+            ParseData::Binary { signed, width } => {
+                write!(f, "b{}{}", if *signed { 2 } else { 1 }, width)
+            }
+            ParseData::Spare(width) => write!(f, "X({})", width),
+            ParseData::Terminator => Display::fmt("@", f),
         }
     }
 }
 
 impl ParseData {
     pub(crate) fn from_str(s: &str) -> Result<(usize, ParseData)> {
+        if s == "@" {
+            return Ok((1, ParseData::Terminator));
+        }
+        if let Some(cap) = BINARY_REGEX.captures(s) {
+            let num = repeat_count(s, cap.get(1))?;
+            let signed = &cap[2] == "2";
+            let width = cap[3].parse().unwrap();
+            return Ok((num, ParseData::Binary { signed, width }));
+        }
+        if let Some(cap) = SPARE_REGEX.captures(s) {
+            let num = repeat_count(s, cap.get(1))?;
+            let width = cap[2]
+                .parse()
+                .map_err(|_| ErrorKind::UnParsableFormatControl(String::from(s)))?;
+            return Ok((num, ParseData::Spare(width)));
+        }
         match FIELD_REGEX.captures(s) {
             Some(cap) => {
                 let num = cap.get(1).map_or(1, |c| c.as_str().parse().unwrap());
@@ -58,19 +292,124 @@ impl ParseData {
                     "A" => ParseType::String,
                     "I" => ParseType::Integer,
                     "R" => ParseType::Float,
+                    "B" => ParseType::Binary,
                     _ => unreachable!(),
                 };
                 let pd = cap.get(3).map_or(ParseData::Variable(typ.clone()), |c| {
                     let c = &c.as_str();
-                    ParseData::Fixed(typ, c[1..c.len() - 1].parse().unwrap())
+                    ParseData::Fixed(typ.clone(), c[1..c.len() - 1].parse().unwrap())
                 });
-                Ok((num, pd))
+                match pd {
+                    // Convert the width from bits to bytes
+                    ParseData::Fixed(ParseType::Binary, bits) => {
+                        if bits % 8 != 0 {
+                            return Err(ErrorKind::UnParsableFormatControl(String::from(s)).into());
+                        }
+                        Ok((num, ParseData::Fixed(typ, bits / 8)))
+                    }
+                    pd => Ok((num, pd)),
+                }
             }
             None => Err(ErrorKind::UnParsableFormatControl(String::from(s)).into()),
         }
     }
 
-    pub(crate) fn parse<R: BufRead>(&self, mut rdr: R) -> Result<Data> {
+    // The width in bytes of fixed width subfields, None for variable length subfields
+    pub(crate) fn byte_width(&self) -> Option<usize> {
+        match &self {
+            ParseData::Fixed(_, size) => Some(*size),
+            ParseData::Variable(_) => None,
+            ParseData::Binary { width, .. } => Some(*width),
+            ParseData::Spare(width) => Some(*width),
+            ParseData::Terminator => Some(0),
+        }
+    }
+
+    // Spares have no array descriptor and are not part of the parsed field
+    pub(crate) fn is_spare(&self) -> bool {
+        matches!(self, ParseData::Spare(_))
+    }
+
+    // The width of a fixed width integer subfield that is too wide for an i64, which is not read
+    pub(crate) fn overflowing_width(&self) -> Option<usize> {
+        match self {
+            ParseData::Fixed(ParseType::Integer, width) if *width > MAX_INTEGER_WIDTH => {
+                Some(*width)
+            }
+            _ => None,
+        }
+    }
+
+    // Whether the subfield has an array descriptor, which spares and terminators have not
+    pub(crate) fn has_descriptor(&self) -> bool {
+        !matches!(self, ParseData::Spare(_) | ParseData::Terminator)
+    }
+
+    // Moves the reader past the subfield without decoding it
+    pub(crate) fn skip<R: BufRead>(&self, mut rdr: R, tes: &TruncEscSeq) -> Result<()> {
+        let width = match self.byte_width() {
+            Some(width) => width,
+            None => {
+                return if read_subfield(&mut rdr, None, tes)
+                    .with_context(|err| ErrorKind::IOError(err.kind()))?
+                {
+                    Ok(())
+                } else {
+                    Err(ErrorKind::IOError(std::io::ErrorKind::UnexpectedEof).into())
+                };
+            }
+        };
+        let skipped = std::io::copy(&mut rdr.take(width as u64), &mut std::io::sink())
+            .with_context(|err| ErrorKind::IOError(err.kind()))?;
+        if skipped < width as u64 {
+            return Err(ErrorKind::IOError(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(())
+    }
+
+    // Encodes the data in the format of the subfield, the counterpart of parse. Strings are written
+    // as they are, i.e. for lexical level 0. Fixed width strings are padded with trailing blanks and
+    // fixed width numbers with leading zeros, a spare is written as blanks whatever the data.
+    pub(crate) fn encode(&self, data: &Data, out: &mut Vec<u8>) -> Result<()> {
+        let mismatch = || ErrorKind::CanNotEncode {
+            format: self.to_string(),
+            value: data.to_string(),
+        };
+        match self {
+            ParseData::Spare(width) => out.resize(out.len() + width, b' '),
+            ParseData::Terminator => (),
+            ParseData::Binary { signed, width } => {
+                let value = match data {
+                    Data::Integer(Some(value)) => *value,
+                    _ => return Err(mismatch().into()),
+                };
+                let bits = 8 * *width as u32;
+                let fits = match (signed, bits) {
+                    (_, 64..=u32::MAX) => true,
+                    (true, bits) => value >= -(1 << (bits - 1)) && value < 1 << (bits - 1),
+                    (false, bits) => value >= 0 && value < 1 << bits,
+                };
+                if !fits {
+                    return Err(mismatch().into());
+                }
+                out.extend_from_slice(&value.to_le_bytes()[..*width]);
+            }
+            ParseData::Variable(t) => {
+                out.extend(encode_value(t, data, None).ok_or_else(mismatch)?);
+                out.push(UNIT_SEPARATOR);
+            }
+            ParseData::Fixed(t, width) => {
+                let bytes = encode_value(t, data, Some(*width)).ok_or_else(mismatch)?;
+                if bytes.len() != *width {
+                    return Err(mismatch().into());
+                }
+                out.extend(bytes);
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn parse<R: BufRead>(&self, mut rdr: R, tes: &TruncEscSeq) -> Result<Data> {
         let (d, t) = match &self {
             ParseData::Fixed(t, size) => {
                 let mut data = vec![0; *size];
@@ -80,26 +419,43 @@ impl ParseData {
             }
             ParseData::Variable(t) => {
                 let mut data = Vec::new();
-                rdr.read_until(UNIT_SEPARATOR, &mut data)
+                // Stopping at EOF instead means the subfield is not terminated
+                if !read_subfield(&mut rdr, Some(&mut data), tes)
+                    .with_context(|err| ErrorKind::IOError(err.kind()))?
+                {
+                    return Err(ErrorKind::IOError(std::io::ErrorKind::UnexpectedEof).into());
+                }
+                (data, t)
+            }
+            ParseData::Binary { signed, width } => {
+                let mut data = vec![0; *width];
+                rdr.read_exact(&mut data)
                     .with_context(|err| ErrorKind::IOError(err.kind()))?;
-                (Vec::from(&data[..data.len() - 1]), t)
+                return Ok(Data::Integer(Some(parse_binary(&data, *signed))));
+            }
+            ParseData::Terminator => return Ok(Data::Binary(Vec::new())),
+            // The fill bytes are returned as they are, it is up to the caller to drop them
+            ParseData::Spare(width) => {
+                let mut data = vec![0; *width];
+                rdr.read_exact(&mut data)
+                    .with_context(|err| ErrorKind::IOError(err.kind()))?;
+                return Ok(Data::Binary(data));
             }
         };
-        let d = std::str::from_utf8(&d).with_context(|&err| ErrorKind::UtfError(err))?;
         match t {
-            ParseType::String => Ok(Data::String(d.to_string())),
+            ParseType::Binary => Ok(Data::Binary(d)),
+            ParseType::Date => Ok(parse_date(parse_to_string(&d, tes)?)),
+            ParseType::String => Ok(Data::String(parse_to_string(&d, tes)?)),
             ParseType::Integer => {
+                let d = parse_to_string(&d, tes)?;
                 if d.is_empty() {
                     Ok(Data::Integer(None))
                 } else {
-                    Ok(Data::Integer(Some(d.parse().with_context(
-                        |err: &std::num::ParseIntError| {
-                            ErrorKind::ParseIntError(err.clone(), d.to_string())
-                        },
-                    )?)))
+                    Ok(Data::Integer(Some(parse_to_i64(&d)?)))
                 }
             }
             ParseType::Float => {
+                let d = parse_to_string(&d, tes)?;
                 if d.is_empty() {
                     Ok(Data::Float(None))
                 } else {
@@ -114,11 +470,349 @@ impl ParseData {
     }
 }
 
+// The bytes of the data as the given type, without terminator. Numbers are padded with leading
+// zeros and strings with trailing blanks to the width, if any. An empty number has no fixed width
+// representation. None if the data does not fit the type.
+fn encode_value(t: &ParseType, data: &Data, width: Option<usize>) -> Option<Vec<u8>> {
+    let w = width.unwrap_or(0);
+    let s = match (t, data) {
+        (ParseType::Binary, Data::Binary(bytes)) => return Some(bytes.clone()),
+        (ParseType::String, Data::String(s)) | (ParseType::Date, Data::String(s)) => {
+            format!("{:w$}", s)
+        }
+        #[cfg(feature = "chrono")]
+        (ParseType::Date, Data::Date(date)) => date.format(DATE_FORMAT).to_string(),
+        (ParseType::Integer, Data::Integer(Some(value))) => format!("{:0w$}", value),
+        (ParseType::Float, Data::Float(Some(value))) => format!("{:0w$}", value),
+        (ParseType::Integer, Data::Integer(None)) | (ParseType::Float, Data::Float(None))
+            if width.is_none() =>
+        {
+            String::new()
+        }
+        _ => return None,
+    };
+    Some(s.into_bytes())
+}
+
+// Parses a YYYYMMDD date, keeping the string if it is malformed
+#[cfg(feature = "chrono")]
+fn parse_date(s: String) -> Data {
+    match chrono::NaiveDate::parse_from_str(&s, DATE_FORMAT) {
+        Ok(date) if s.len() == 8 => Data::Date(date),
+        _ => Data::String(s),
+    }
+}
+
+#[cfg(not(feature = "chrono"))]
+fn parse_date(s: String) -> Data {
+    Data::String(s)
+}
+
+// Reads a variable length subfield into data, up to and including the unit separator, or the
+// field terminator when the last subfield of a field is terminated by it. The terminator is not
+// kept in data, without data the subfield is skipped. Returns false if the reader ended before a
+// terminator. Below lexical level 2 the terminator is found per byte, so it may split a multi-byte
+// UTF-8 character of a corrupt subfield, which the decoding rejects with an error.
+fn read_subfield<R: BufRead>(
+    rdr: &mut R,
+    mut data: Option<&mut Vec<u8>>,
+    tes: &TruncEscSeq,
+) -> std::io::Result<bool> {
+    if *tes == TruncEscSeq::LE2 {
+        return read_ucs2_subfield(rdr, data);
+    }
+    loop {
+        let (done, used) = {
+            let available = match rdr.fill_buf() {
+                Ok(available) => available,
+                Err(ref err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            if available.is_empty() {
+                return Ok(false);
+            }
+            match available
+                .iter()
+                .position(|&b| b == UNIT_SEPARATOR || b == RECORD_SEPARATOR)
+            {
+                Some(i) => {
+                    if let Some(data) = data.as_mut() {
+                        data.extend_from_slice(&available[..i]);
+                    }
+                    (true, i + 1)
+                }
+                None => {
+                    if let Some(data) = data.as_mut() {
+                        data.extend_from_slice(available);
+                    }
+                    (false, available.len())
+                }
+            }
+        };
+        rdr.consume(used);
+        if done {
+            return Ok(true);
+        }
+    }
+}
+
+// Like read_subfield for lexical level 2, where the terminators are the UCS-2 code units of the
+// unit separator and field terminator, i.e. 0x1F 0x00 and 0x1E 0x00. They are found per code unit,
+// so a character like U+011F, whose low byte is a separator, is not taken for a terminator.
+fn read_ucs2_subfield<R: BufRead>(
+    rdr: &mut R,
+    mut data: Option<&mut Vec<u8>>,
+) -> std::io::Result<bool> {
+    loop {
+        let mut unit = [0; 2];
+        let mut read = 0;
+        while read < unit.len() {
+            match rdr.read(&mut unit[read..]) {
+                Ok(0) => break,
+                Ok(n) => read += n,
+                Err(ref err) if err.kind() == std::io::ErrorKind::Interrupted => (),
+                Err(err) => return Err(err),
+            }
+        }
+        if read < unit.len() {
+            if let Some(data) = data.as_mut() {
+                data.extend_from_slice(&unit[..read]);
+            }
+            return Ok(false);
+        }
+        if unit == [UNIT_SEPARATOR, 0] || unit == [RECORD_SEPARATOR, 0] {
+            return Ok(true);
+        }
+        if let Some(data) = data.as_mut() {
+            data.extend_from_slice(&unit);
+        }
+    }
+}
+
+// Parses a signed integer subfield, unlike the lengths and offsets of the DDR which are parsed with
+// parse_to_usize. A leading - or + is allowed, e.g. longitudes west of Greenwich are negative.
+fn parse_to_i64(s: &str) -> Result<i64> {
+    Ok(s.parse().with_context(|err: &std::num::ParseIntError| {
+        ErrorKind::ParseIntError(err.clone(), s.to_string())
+    })?)
+}
+
+// Decodes a little-endian integer of at most 8 bytes, sign extending it if signed.
+fn parse_binary(bytes: &[u8], signed: bool) -> i64 {
+    let negative = signed && bytes.last().is_some_and(|&b| b & 0x80 != 0);
+    let mut buf = if negative { [0xff; 8] } else { [0; 8] };
+    buf[..bytes.len()].copy_from_slice(bytes);
+    i64::from_le_bytes(buf)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::io::Cursor;
 
+    // Encodes the data and parses it back
+    fn encode(pd: &ParseData, data: &Data) -> Vec<u8> {
+        let mut out = Vec::new();
+        pd.encode(data, &mut out).unwrap();
+        let parsed = pd.parse(Cursor::new(&out[..]), &TruncEscSeq::LE0).unwrap();
+        if !pd.is_spare() {
+            assert_eq!(&parsed, data);
+        }
+        out
+    }
+
+    #[test]
+    fn data_accessors() {
+        // Present
+        assert_eq!(Data::Integer(Some(42)).as_i64(), Some(42));
+        assert_eq!(Data::Float(Some(1.5)).as_f64(), Some(1.5));
+        assert_eq!(Data::String("V01".to_string()).as_str(), Some("V01"));
+        assert!(!Data::Integer(Some(0)).is_null());
+        // Null
+        assert_eq!(Data::Integer(None).as_i64(), None);
+        assert_eq!(Data::Float(None).as_f64(), None);
+        assert_eq!(Data::String(String::new()).as_str(), Some(""));
+        assert!(Data::Integer(None).is_null() && Data::Float(None).is_null());
+        assert!(!Data::String(String::new()).is_null());
+        // Wrong type
+        assert_eq!(Data::Float(Some(42.0)).as_i64(), None);
+        assert_eq!(Data::Integer(Some(1)).as_f64(), None);
+        assert_eq!(Data::Integer(Some(1)).as_str(), None);
+        assert_eq!(Data::Binary(vec![1]).as_i64(), None);
+    }
+
+    #[test]
+    fn encode_data() {
+        let string = |s: &str| Data::String(s.to_string());
+        assert_eq!(
+            encode(&ParseData::Fixed(ParseType::String, 3), &string("V01")),
+            b"V01"
+        );
+        assert_eq!(
+            encode(
+                &ParseData::Fixed(ParseType::Integer, 10),
+                &Data::Integer(Some(2))
+            ),
+            b"0000000002"
+        );
+        assert_eq!(
+            encode(
+                &ParseData::Fixed(ParseType::Integer, 5),
+                &Data::Integer(Some(-42))
+            ),
+            b"-0042"
+        );
+        assert_eq!(
+            encode(
+                &ParseData::Fixed(ParseType::Float, 6),
+                &Data::Float(Some(59.5))
+            ),
+            b"0059.5"
+        );
+        assert_eq!(
+            encode(
+                &ParseData::Fixed(ParseType::Binary, 2),
+                &Data::Binary(vec![1, 2])
+            ),
+            b"\x01\x02"
+        );
+        assert_eq!(
+            encode(
+                &ParseData::Variable(ParseType::String),
+                &string("CATALOG.031")
+            ),
+            b"CATALOG.031\x1f"
+        );
+        assert_eq!(
+            encode(&ParseData::Variable(ParseType::Float), &Data::Float(None)),
+            b"\x1f"
+        );
+        assert_eq!(
+            encode(
+                &ParseData::Variable(ParseType::Integer),
+                &Data::Integer(Some(7))
+            ),
+            b"7\x1f"
+        );
+        assert_eq!(
+            encode(
+                &ParseData::Binary {
+                    signed: true,
+                    width: 4
+                },
+                &Data::Integer(Some(-102500000))
+            ),
+            (-102500000i32).to_le_bytes()
+        );
+        assert_eq!(
+            encode(
+                &ParseData::Binary {
+                    signed: false,
+                    width: 2
+                },
+                &Data::Integer(Some(65535))
+            ),
+            [0xff, 0xff]
+        );
+        assert_eq!(
+            encode(&ParseData::Spare(2), &Data::Binary(Vec::new())),
+            b"  "
+        );
+        // Fixed width strings are padded, so they parse back with the blanks
+        let mut out = Vec::new();
+        ParseData::Fixed(ParseType::String, 4)
+            .encode(&string("AB"), &mut out)
+            .unwrap();
+        assert_eq!(out, b"AB  ");
+    }
+
+    #[test]
+    fn encode_mismatch() {
+        let fails = |pd: ParseData, data: Data| {
+            let mut out = Vec::new();
+            match pd.encode(&data, &mut out).unwrap_err().kind() {
+                ErrorKind::CanNotEncode { .. } => (),
+                kind => panic!("Unexpected error: {}", kind),
+            }
+        };
+        fails(
+            ParseData::Fixed(ParseType::Integer, 2),
+            Data::String("12".to_string()),
+        );
+        fails(
+            ParseData::Variable(ParseType::Float),
+            Data::Integer(Some(1)),
+        );
+        fails(
+            ParseData::Fixed(ParseType::String, 2),
+            Data::String("ABC".to_string()),
+        );
+        fails(
+            ParseData::Fixed(ParseType::Integer, 2),
+            Data::Integer(Some(100)),
+        );
+        fails(ParseData::Fixed(ParseType::Integer, 2), Data::Integer(None));
+        fails(
+            ParseData::Fixed(ParseType::Binary, 2),
+            Data::Binary(vec![1]),
+        );
+        fails(
+            ParseData::Binary {
+                signed: false,
+                width: 1,
+            },
+            Data::Integer(Some(-1)),
+        );
+        fails(
+            ParseData::Binary {
+                signed: true,
+                width: 1,
+            },
+            Data::Integer(Some(128)),
+        );
+        fails(
+            ParseData::Variable(ParseType::String),
+            Data::Array(Vec::new()),
+        );
+    }
+
+    #[test]
+    fn parse_split_characters() {
+        // A UTF-8 sequence cut short by the unit separator
+        let err = ParseData::Variable(ParseType::String)
+            .parse(Cursor::new(&b"\xe2\x82\x1f"[..]), &TruncEscSeq::LE0)
+            .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::UtfError(_)));
+        // A UTF-8 sequence cut short by the width of the subfield
+        let err = ParseData::Fixed(ParseType::String, 3)
+            .parse(Cursor::new(&b"ab\xc3\xa5"[..]), &TruncEscSeq::LE0)
+            .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::UtfError(_)));
+    }
+
+    #[test]
+    fn parse_spare() {
+        assert_eq!(
+            ParseData::from_str("X(3)").unwrap(),
+            (1, ParseData::Spare(3))
+        );
+        assert_eq!(
+            ParseData::from_str("2X(1)").unwrap(),
+            (2, ParseData::Spare(1))
+        );
+        assert!(ParseData::from_str("X").is_err());
+        for s in &["X(99999999999999999999)", "99999999999999999999X(1)"] {
+            let err = ParseData::from_str(s).unwrap_err();
+            assert!(matches!(err.kind(), ErrorKind::UnParsableFormatControl(_)));
+        }
+        assert_eq!(ParseData::Spare(3).to_string(), "X(3)");
+        let mut cur = Cursor::new("   1".as_bytes());
+        ParseData::Spare(3)
+            .parse(&mut cur, &TruncEscSeq::LE0)
+            .unwrap();
+        assert_eq!(cur.position(), 3);
+    }
+
     #[test]
     fn parsedata() {
         assert_eq!(
@@ -157,66 +851,391 @@ mod tests {
             ParseData::from_str("1R(5)").unwrap(),
             (1, ParseData::Fixed(ParseType::Float, 5))
         );
+        assert_eq!(
+            ParseData::from_str("b11").unwrap(),
+            (
+                1,
+                ParseData::Binary {
+                    signed: false,
+                    width: 1
+                }
+            )
+        );
+        assert_eq!(
+            ParseData::from_str("2b24").unwrap(),
+            (
+                2,
+                ParseData::Binary {
+                    signed: true,
+                    width: 4
+                }
+            )
+        );
+        assert!(ParseData::from_str("b13").is_err());
+        let err = ParseData::from_str("99999999999999999999b11").unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::UnParsableFormatControl(_)));
+        assert_eq!(
+            ParseData::from_str("B(40)").unwrap(),
+            (1, ParseData::Fixed(ParseType::Binary, 5))
+        );
+        assert_eq!(
+            ParseData::from_str("B").unwrap(),
+            (1, ParseData::Variable(ParseType::Binary))
+        );
+        assert!(ParseData::from_str("B(12)").is_err());
+    }
+
+    #[test]
+    fn byte_width() {
+        assert_eq!(ParseData::Fixed(ParseType::String, 3).byte_width(), Some(3));
+        assert_eq!(
+            ParseData::Binary {
+                signed: true,
+                width: 4
+            }
+            .byte_width(),
+            Some(4)
+        );
+        assert_eq!(ParseData::Variable(ParseType::Float).byte_width(), None);
+    }
+
+    #[test]
+    fn display_parsedata() {
+        assert_eq!(ParseData::Fixed(ParseType::String, 3).to_string(), "A(3)");
+        assert_eq!(
+            ParseData::Fixed(ParseType::Integer, 10).to_string(),
+            "I(10)"
+        );
+        assert_eq!(ParseData::Variable(ParseType::Float).to_string(), "R");
+        assert_eq!(ParseData::Fixed(ParseType::Binary, 5).to_string(), "B(40)");
+    }
+
+    #[test]
+    fn data_equality() {
+        assert_eq!(Data::Float(Some(f64::NAN)), Data::Float(Some(f64::NAN)));
+        assert_ne!(Data::Float(Some(0.0)), Data::Float(Some(-0.0)));
+        assert_eq!(Data::Float(None), Data::Float(None));
+        assert_ne!(Data::Float(None), Data::Integer(None));
+        assert_ne!(
+            Data::String("1".to_string()),
+            Data::Binary("1".as_bytes().to_vec())
+        );
+    }
+
+    #[test]
+    fn read_signed_integer() {
+        assert_eq!(
+            ParseData::Fixed(ParseType::Integer, 8)
+                .parse(Cursor::new("-1234567".as_bytes()), &TruncEscSeq::LE0)
+                .unwrap(),
+            Data::Integer(Some(-1234567))
+        );
+        assert_eq!(
+            ParseData::Variable(ParseType::Integer)
+                .parse(Cursor::new("+42\x1f".as_bytes()), &TruncEscSeq::LE0)
+                .unwrap(),
+            Data::Integer(Some(42))
+        );
+        assert!(ParseData::Fixed(ParseType::Integer, 3)
+            .parse(Cursor::new("--1".as_bytes()), &TruncEscSeq::LE0)
+            .is_err());
     }
 
     #[test]
     fn read_data() {
         assert_eq!(
             ParseData::Fixed(ParseType::Integer, 5)
-                .parse(Cursor::new("00001".as_bytes()))
+                .parse(Cursor::new("00001".as_bytes()), &TruncEscSeq::LE0)
                 .unwrap(),
             Data::Integer(Some(1))
         );
         assert_eq!(
             ParseData::Fixed(ParseType::String, 5)
-                .parse(Cursor::new("Hejsa".as_bytes()))
+                .parse(Cursor::new("Hejsa".as_bytes()), &TruncEscSeq::LE0)
                 .unwrap(),
             Data::String(String::from("Hejsa"))
         );
         assert_eq!(
             ParseData::Fixed(ParseType::Float, 5)
-                .parse(Cursor::new("0.005".as_bytes()))
+                .parse(Cursor::new("0.005".as_bytes()), &TruncEscSeq::LE0)
                 .unwrap(),
             Data::Float(Some(0.005))
         );
         assert_eq!(
             ParseData::Variable(ParseType::Integer)
-                .parse(Cursor::new(&[
-                    '0' as u8,
-                    '0' as u8,
-                    '0' as u8,
-                    '0' as u8,
-                    '1' as u8,
-                    UNIT_SEPARATOR,
-                ]))
+                .parse(
+                    Cursor::new(&[
+                        '0' as u8,
+                        '0' as u8,
+                        '0' as u8,
+                        '0' as u8,
+                        '1' as u8,
+                        UNIT_SEPARATOR,
+                    ]),
+                    &TruncEscSeq::LE0
+                )
                 .unwrap(),
             Data::Integer(Some(1))
         );
         assert_eq!(
             ParseData::Variable(ParseType::String)
-                .parse(Cursor::new(&[
-                    'H' as u8,
-                    'e' as u8,
-                    'j' as u8,
-                    's' as u8,
-                    'a' as u8,
-                    UNIT_SEPARATOR,
-                ]))
+                .parse(
+                    Cursor::new(&[
+                        'H' as u8,
+                        'e' as u8,
+                        'j' as u8,
+                        's' as u8,
+                        'a' as u8,
+                        UNIT_SEPARATOR,
+                    ]),
+                    &TruncEscSeq::LE0
+                )
                 .unwrap(),
             Data::String(String::from("Hejsa"))
         );
         assert_eq!(
             ParseData::Variable(ParseType::Float)
-                .parse(Cursor::new(&[
-                    '0' as u8,
-                    '.' as u8,
-                    '0' as u8,
-                    '0' as u8,
-                    '5' as u8,
-                    UNIT_SEPARATOR,
-                ]))
+                .parse(
+                    Cursor::new(&[
+                        '0' as u8,
+                        '.' as u8,
+                        '0' as u8,
+                        '0' as u8,
+                        '5' as u8,
+                        UNIT_SEPARATOR,
+                    ]),
+                    &TruncEscSeq::LE0
+                )
                 .unwrap(),
             Data::Float(Some(0.005))
         );
     }
+
+    #[test]
+    fn read_empty_data() {
+        let empty = [UNIT_SEPARATOR];
+        let parse = |t: ParseType| {
+            ParseData::Variable(t)
+                .parse(Cursor::new(&empty), &TruncEscSeq::LE0)
+                .unwrap()
+        };
+        assert_eq!(parse(ParseType::String), Data::String(String::new()));
+        assert_eq!(parse(ParseType::Integer), Data::Integer(None));
+        assert_eq!(parse(ParseType::Float), Data::Float(None));
+        assert_eq!(parse(ParseType::Binary), Data::Binary(Vec::new()));
+        assert_eq!(
+            ParseData::Variable(ParseType::String)
+                .parse(Cursor::new(&[UNIT_SEPARATOR, 0x00]), &TruncEscSeq::LE2)
+                .unwrap(),
+            Data::String(String::new())
+        );
+    }
+
+    #[test]
+    fn read_ucs2_subfields() {
+        // The low bytes of U+011F, U+041E and U+041F equal the separators
+        let mut bytes = "ğОП"
+            .encode_utf16()
+            .flat_map(|c| c.to_le_bytes().to_vec())
+            .collect::<Vec<u8>>();
+        bytes.extend_from_slice(&[UNIT_SEPARATOR, 0x00, b'A', 0x00, RECORD_SEPARATOR, 0x00]);
+        let mut cur = Cursor::new(&bytes);
+        let parser = ParseData::Variable(ParseType::String);
+        assert_eq!(
+            parser.parse(&mut cur, &TruncEscSeq::LE2).unwrap(),
+            Data::String(String::from("ğОП"))
+        );
+        assert_eq!(
+            parser.parse(&mut cur, &TruncEscSeq::LE2).unwrap(),
+            Data::String(String::from("A"))
+        );
+        assert_eq!(cur.position(), bytes.len() as u64);
+        let mut cur = Cursor::new(&bytes);
+        parser.skip(&mut cur, &TruncEscSeq::LE2).unwrap();
+        assert_eq!(cur.position(), 8);
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn read_date() {
+        let date = ParseData::Fixed(ParseType::Date, 8)
+            .parse(Cursor::new("20230115".as_bytes()), &TruncEscSeq::LE0)
+            .unwrap();
+        assert_eq!(date.as_date(), chrono::NaiveDate::from_ymd_opt(2023, 1, 15));
+        assert_eq!(date.to_string(), "20230115");
+        assert_eq!(
+            ParseData::Fixed(ParseType::Date, 8)
+                .parse(Cursor::new("20231315".as_bytes()), &TruncEscSeq::LE0)
+                .unwrap(),
+            Data::String("20231315".to_string())
+        );
+        assert_eq!(Data::String("20230115".to_string()).as_date(), None);
+    }
+
+    #[test]
+    #[cfg(not(feature = "chrono"))]
+    fn read_date() {
+        assert_eq!(
+            ParseData::Fixed(ParseType::Date, 8)
+                .parse(Cursor::new("20230115".as_bytes()), &TruncEscSeq::LE0)
+                .unwrap(),
+            Data::String("20230115".to_string())
+        );
+    }
+
+    #[test]
+    fn read_variable_subfields() {
+        let mut cur = Cursor::new("CATALOG.031\x1fV01X01\x1f42\x1e".as_bytes());
+        let parser = ParseData::Variable(ParseType::String);
+        assert_eq!(
+            parser.parse(&mut cur, &TruncEscSeq::LE0).unwrap(),
+            Data::String("CATALOG.031".to_string())
+        );
+        assert_eq!(cur.position(), 12);
+        assert_eq!(
+            parser.parse(&mut cur, &TruncEscSeq::LE0).unwrap(),
+            Data::String("V01X01".to_string())
+        );
+        assert_eq!(cur.position(), 19);
+        // The last subfield may be terminated by the field terminator
+        assert_eq!(
+            ParseData::Variable(ParseType::Integer)
+                .parse(&mut cur, &TruncEscSeq::LE0)
+                .unwrap(),
+            Data::Integer(Some(42))
+        );
+        assert_eq!(cur.position(), 22);
+    }
+
+    #[test]
+    fn skip_subfields() {
+        let mut cur = Cursor::new("CD0000000001CATALOG.031\x1fV01\x1f".as_bytes());
+        ParseData::Fixed(ParseType::String, 2)
+            .skip(&mut cur, &TruncEscSeq::LE0)
+            .unwrap();
+        ParseData::Fixed(ParseType::Integer, 10)
+            .skip(&mut cur, &TruncEscSeq::LE0)
+            .unwrap();
+        ParseData::Variable(ParseType::String)
+            .skip(&mut cur, &TruncEscSeq::LE0)
+            .unwrap();
+        assert_eq!(cur.position(), 24);
+        assert_eq!(
+            ParseData::Variable(ParseType::String)
+                .parse(&mut cur, &TruncEscSeq::LE0)
+                .unwrap(),
+            Data::String("V01".to_string())
+        );
+        assert!(ParseData::Fixed(ParseType::Integer, 1)
+            .skip(&mut cur, &TruncEscSeq::LE0)
+            .is_err());
+        assert!(ParseData::Variable(ParseType::Integer)
+            .skip(&mut cur, &TruncEscSeq::LE0)
+            .is_err());
+    }
+
+    #[test]
+    fn read_unterminated_data() {
+        assert!(ParseData::Variable(ParseType::String)
+            .parse(Cursor::new("Hejsa".as_bytes()), &TruncEscSeq::LE0)
+            .is_err());
+        assert!(ParseData::Variable(ParseType::String)
+            .parse(Cursor::new(&[]), &TruncEscSeq::LE0)
+            .is_err());
+    }
+
+    #[test]
+    fn read_binary_data() {
+        let mut cur = Cursor::new(&[0x64, 0xe8, 0x03, 0x00, 0x00, 0xfe, 0xff, 0xff, 0xff]);
+        assert_eq!(
+            ParseData::Binary {
+                signed: false,
+                width: 1
+            }
+            .parse(&mut cur, &TruncEscSeq::LE0)
+            .unwrap(),
+            Data::Integer(Some(100))
+        );
+        assert_eq!(
+            ParseData::Binary {
+                signed: false,
+                width: 4
+            }
+            .parse(&mut cur, &TruncEscSeq::LE0)
+            .unwrap(),
+            Data::Integer(Some(1000))
+        );
+        assert_eq!(
+            ParseData::Binary {
+                signed: true,
+                width: 4
+            }
+            .parse(&mut cur, &TruncEscSeq::LE0)
+            .unwrap(),
+            Data::Integer(Some(-2))
+        );
+    }
+
+    #[test]
+    fn read_raw_binary_data() {
+        let bytes = [0x02, 0x1e, 0x1f, 0x00, 0xff];
+        let data = ParseData::Fixed(ParseType::Binary, 5)
+            .parse(Cursor::new(&bytes), &TruncEscSeq::LE0)
+            .unwrap();
+        assert_eq!(data.as_bytes(), Some(&bytes[..]));
+        assert_eq!(data.to_string(), "021E1F00FF");
+        let data = ParseData::Variable(ParseType::Binary)
+            .parse(
+                Cursor::new(&[0x02, 0x00, UNIT_SEPARATOR]),
+                &TruncEscSeq::LE0,
+            )
+            .unwrap();
+        assert_eq!(data.as_bytes(), Some(&[0x02, 0x00][..]));
+        assert_eq!(Data::Integer(Some(1)).as_bytes(), None);
+    }
+
+    #[test]
+    fn read_latin1_data() {
+        assert_eq!(
+            ParseData::Variable(ParseType::String)
+                .parse(
+                    Cursor::new(&[0xE5, 0xE4, 0xF6, UNIT_SEPARATOR]),
+                    &TruncEscSeq::LE1
+                )
+                .unwrap(),
+            Data::String(String::from("åäö"))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde_json")]
+    fn data_to_json_value() {
+        use serde_json::{json, Value};
+        assert_eq!(Value::from(&Data::Integer(Some(-7))), json!(-7));
+        assert_eq!(Value::from(&Data::Integer(None)), Value::Null);
+        assert_eq!(Value::from(&Data::Float(Some(0.5))), json!(0.5));
+        assert_eq!(Value::from(&Data::Float(None)), Value::Null);
+        assert_eq!(Value::from(&Data::Float(Some(f64::NAN))), Value::Null);
+        assert_eq!(
+            Value::from(&Data::String("CATALOG.031".to_string())),
+            json!("CATALOG.031")
+        );
+        assert_eq!(Value::from(&Data::String(String::new())), json!(""));
+        assert_eq!(Value::from(&Data::Binary(vec![1, 255])), json!([1, 255]));
+        assert_eq!(Value::from(&Data::Binary(vec![])), json!([]));
+        let mut element = Field::new();
+        element.insert("YCOO".to_string(), Data::Integer(Some(1)));
+        element.insert("XCOO".to_string(), Data::Integer(Some(2)));
+        assert_eq!(
+            Value::from(&Data::Array(vec![element])),
+            json!([{"YCOO": 1, "XCOO": 2}])
+        );
+        #[cfg(feature = "chrono")]
+        assert_eq!(
+            Value::from(&Data::Date(
+                chrono::NaiveDate::from_ymd_opt(2023, 1, 15).unwrap()
+            )),
+            json!("20230115")
+        );
+    }
 }
@@ -11,6 +11,15 @@ impl Error {
     pub fn kind(&self) -> &ErrorKind {
         self.inner.get_context()
     }
+
+    /// The underlying failure this error was raised for, if any.
+    pub fn cause(&self) -> Option<&dyn Fail> {
+        self.inner.cause()
+    }
+
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        self.inner.backtrace()
+    }
 }
 
 impl fmt::Display for Error {
@@ -19,13 +28,28 @@ impl fmt::Display for Error {
     }
 }
 
-impl Fail for Error {
-    fn cause(&self) -> Option<&Fail> {
-        self.inner.cause()
-    }
-
-    fn backtrace(&self) -> Option<&Backtrace> {
-        self.inner.backtrace()
+// Fail is implemented through the blanket impl for std errors, so `Error` works with both `?` into
+// a `Box<dyn std::error::Error>` and the `failure` context helpers
+impl std::error::Error for Error {
+    // The causes are kept as `Fail`, so the std errors they were created from are downcast back
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        let cause = self.inner.cause()?;
+        if let Some(err) = cause.downcast_ref::<Error>() {
+            return Some(err);
+        }
+        if let Some(err) = cause.downcast_ref::<std::io::Error>() {
+            return Some(err);
+        }
+        if let Some(err) = cause.downcast_ref::<std::str::Utf8Error>() {
+            return Some(err);
+        }
+        if let Some(err) = cause.downcast_ref::<std::string::FromUtf8Error>() {
+            return Some(err);
+        }
+        if let Some(err) = cause.downcast_ref::<std::num::ParseIntError>() {
+            return Some(err);
+        }
+        None
     }
 }
 
@@ -44,19 +68,51 @@ impl From<Context<ErrorKind>> for Error {
 }
 
 #[derive(Fail, Debug)]
+#[non_exhaustive]
 pub enum ErrorKind {
+    #[fail(display = "Missing or invalid coordinate subfield '{}'", _0)]
+    BadCoordinate(String),
     #[fail(display = "Bad Data Structure Code: {}", _0)]
     BadDataStructureCode(String),
     #[fail(display = "Bad Data Type Code: {}", _0)]
     BadDataTypeCode(String),
     #[fail(display = "Bad Directory Data")]
     BadDirectoryData,
+    #[fail(
+        display = "Field '{}' has {} array descriptors but {} format controls",
+        field, descriptors, formats
+    )]
+    DescriptorFormatMismatch {
+        field: String,
+        descriptors: usize,
+        formats: usize,
+    },
+    #[fail(display = "Directory entry '{}' points outside the field area", _0)]
+    DirectoryOutOfBounds(String),
+    #[fail(display = "Bad CRC: '{}'", _0)]
+    BadCrc(String),
+    #[fail(display = "Bad Implementation: '{}'", _0)]
+    BadImplementation(String),
+    #[fail(display = "Bad Interchange Level: '{}'", _0)]
+    BadInterchangeLevel(String),
+    #[fail(display = "Bad Record Name: '{}'", _0)]
+    BadRecordName(String),
+    #[fail(display = "Bad foreign pointer: {}", _0)]
+    BadRecordPointer(String),
+    #[fail(display = "Bad attribute: {}", _0)]
+    BadAttribute(String),
     #[fail(display = "Bad Truncated Escape Sequence: '{}'", _0)]
     BadTruncEscSeq(String),
+    #[fail(display = "Bad Extended Character Set Indicator: '{}'", _0)]
+    BadCharacterSet(String),
     #[fail(display = "Bad Field Control")]
     BadFieldControl,
+    #[fail(display = "The coordinate field does not repeat")]
+    CoordinatesNotRepeating,
     #[fail(display = "Could Not Parse The Catalog File")]
     CouldNotParseCatalog,
+    #[fail(display = "Could Not Parse The Cell File")]
+    CouldNotParseCell,
     #[fail(display = "Could Not Parse Name")]
     CouldNotParseName,
     #[fail(display = "Empty Format Controls")]
@@ -67,25 +123,100 @@ pub enum ErrorKind {
     InvalidDR,
     #[fail(display = "The Leader is not correct.")]
     InvalidLeader,
+    #[fail(
+        display = "Could not parse subfield '{}' of field '{}' at byte {}",
+        subfield, field, offset
+    )]
+    FieldParse {
+        field: String,
+        subfield: String,
+        offset: u64,
+    },
+    #[fail(
+        display = "The catalog does not follow the S-57 profile, missing: {:?}, unexpected: {:?}",
+        missing, unexpected
+    )]
+    InvalidCatalogProfile {
+        missing: Vec<String>,
+        unexpected: Vec<String>,
+    },
     #[fail(display = "Invalid Field with name: '{}'", _0)]
     InvalidDDF(String),
     #[fail(display = "A Data Descriptive Field is not correct")]
     InvalidDDFS,
+    #[fail(display = "The File Control Field is not correct")]
+    InvalidFileControlField,
     #[fail(display = "Invalid Header")]
     InvalidHeader,
     #[fail(display = "EOF")]
     EOF,
+    #[fail(
+        display = "The record is truncated, expected {} bytes but got {}",
+        expected, got
+    )]
+    TruncatedRecord { expected: usize, got: usize },
+    #[fail(display = "Field '{}' is not terminated by a record separator", _0)]
+    MissingFieldTerminator(String),
+    #[fail(display = "Missing subfield '{}' of field '{}'", subfield, field)]
+    MissingSubfield { field: String, subfield: String },
+    #[fail(display = "The record has no CRC")]
+    MissingCrc,
     #[fail(display = "IOError: {:?}", _0)]
     IOError(IoError),
     #[fail(display = "Could not parse '{}' as integer.", _1)]
     ParseIntError(#[cause] std::num::ParseIntError, String),
     #[fail(display = "Could not parse '{}' as float.", _1)]
     ParseFloatError(#[cause] std::num::ParseFloatError, String),
+    #[fail(display = "Unknown Record Update Instruction: {}", _0)]
+    BadUpdateInstruction(i64),
+    #[fail(display = "Bad update control for field '{}'", _0)]
+    BadUpdateControl(String),
+    #[fail(display = "The data set has no DSID record")]
+    MissingDsid,
+    #[fail(
+        display = "Update is for edition '{}' but the base cell is edition '{}'",
+        found, expected
+    )]
+    UpdateEditionMismatch { expected: String, found: String },
+    #[fail(
+        display = "Expected update number '{}' but found '{}'",
+        expected, found
+    )]
+    UpdateOutOfOrder { expected: String, found: String },
+    #[fail(display = "Update inserts record {}/{} which already exists", _0, _1)]
+    UpdateTargetExists(i64, i64),
+    #[fail(display = "Update refers to record {}/{} which does not exist", _0, _1)]
+    UpdateTargetMissing(i64, i64),
     #[fail(display = "Can not parse Format Control '{}'", _0)]
     UnParsableFormatControl(String),
+    #[fail(
+        display = "Can not encode '{}' with the format control {}",
+        value, format
+    )]
+    CanNotEncode { format: String, value: String },
+    #[fail(display = "The DDR does not describe field '{}'", _0)]
+    UnknownField(String),
     #[fail(display = "UtfError")]
     UtfError(#[cause] std::str::Utf8Error),
-    #[doc(hidden)]
-    #[fail(display = "")]
-    __Nonexhaustive,
+    #[fail(display = "Could not decode UCS-2 string")]
+    Ucs2Error,
+    #[fail(
+        display = "Coordinate out of range, longitude {} latitude {}",
+        lon, lat
+    )]
+    CoordinateOutOfRange { lon: f64, lat: f64 },
+    #[fail(
+        display = "Subfield '{}' of width {} overflows a 64 bit integer",
+        field, width
+    )]
+    NumericOverflow { field: String, width: usize },
+    #[fail(
+        display = "Field '{}' is {} bytes long, but its subfields take {}",
+        field, expected, consumed
+    )]
+    FieldLengthMismatch {
+        field: String,
+        expected: usize,
+        consumed: usize,
+    },
 }
@@ -0,0 +1,240 @@
+//! The iso8211.rs holds the byte slice parsers for the fixed parts of an ISO 8211 record: the
+//! leader, the directory, the field controls and the format controls. They neither allocate nor
+//! use `std`, the results borrow from the given slice or are written into buffers of the caller,
+//! so they are available without the `std` feature. `Catalog` builds on them.
+use core::fmt;
+
+/// The length of the leader at the start of every record.
+pub const LEADER_LENGTH: usize = 24;
+
+/// Why a slice could not be parsed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// The slice is shorter than the structure it should hold.
+    TooShort,
+    /// A length, position or size is not a number.
+    InvalidNumber,
+    /// A size of the entry map of the leader is zero.
+    InvalidEntryMap,
+    /// The directory is not a whole number of entries.
+    MisalignedDirectory,
+    /// The buffer of the caller can not hold all the results.
+    BufferTooSmall,
+    /// The format controls are not a parenthesied list with balanced parenthesies.
+    InvalidFormatControls,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self {
+            ParseError::TooShort => "The slice is too short",
+            ParseError::InvalidNumber => "Not a number",
+            ParseError::InvalidEntryMap => "Empty entry map size",
+            ParseError::MisalignedDirectory => "The directory does not end on an entry",
+            ParseError::BufferTooSmall => "The buffer is too small",
+            ParseError::InvalidFormatControls => "Can not parse the format controls",
+        };
+        f.write_str(msg)
+    }
+}
+
+pub type Result<T> = core::result::Result<T, ParseError>;
+
+/// The sizes of the parts of a directory entry, from the entry map of the leader.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct EntryMap {
+    pub length_size: usize,   // Size Of Field Length Field
+    pub position_size: usize, // Size Of Field Position Field
+    pub tag_size: usize,      // Size Of Field Tag Field
+}
+
+impl EntryMap {
+    /// The size of a directory entry.
+    pub fn entry_size(&self) -> usize {
+        self.tag_size + self.length_size + self.position_size
+    }
+}
+
+/// The leader of a record with its bytes kept as they are, except for the numbers.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Leader {
+    pub record_length: usize,
+    pub interchange_level: u8,
+    pub leader_identifier: u8,
+    pub extension_indicator: u8,
+    pub version: u8,
+    pub application_indicator: u8,
+    pub field_control_length: [u8; 2],
+    pub base_address: usize,
+    pub character_set: [u8; 3],
+    pub entry_map: EntryMap,
+    pub reserved: u8,
+}
+
+/// An entry of the directory, the tag is not decoded.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct DirectoryEntry<'a> {
+    pub tag: &'a [u8],
+    pub length: usize,
+    pub position: usize,
+}
+
+/// The field controls at the start of a field of the DDR, the codes are not decoded. The
+/// truncated escape sequence is empty when the field controls are only 6 bytes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct FieldControls<'a> {
+    pub data_structure: u8,
+    pub data_type: u8,
+    pub auxiliary: &'a [u8],
+    pub printable_graphics: &'a [u8],
+    pub escape_sequence: &'a [u8],
+}
+
+/// Parses a number surrounded by ASCII whitespace, as some producers pad the numbers of the
+/// leader and directory with blanks instead of leading zeros.
+pub fn parse_number(bytes: &[u8]) -> Result<usize> {
+    let start = bytes.iter().position(|b| !b.is_ascii_whitespace());
+    let end = bytes.iter().rposition(|b| !b.is_ascii_whitespace());
+    let digits = match (start, end) {
+        (Some(start), Some(end)) => &bytes[start..=end],
+        _ => return Err(ParseError::InvalidNumber),
+    };
+    digits.iter().try_fold(0usize, |n, &b| {
+        if !b.is_ascii_digit() {
+            return Err(ParseError::InvalidNumber);
+        }
+        n.checked_mul(10)
+            .and_then(|n| n.checked_add(usize::from(b - b'0')))
+            .ok_or(ParseError::InvalidNumber)
+    })
+}
+
+/// Parses the 24 bytes of a leader, starting with the record length.
+pub fn parse_leader(bytes: &[u8]) -> Result<Leader> {
+    if bytes.len() < LEADER_LENGTH {
+        return Err(ParseError::TooShort);
+    }
+    let record_length = parse_number(&bytes[..5])?;
+    parse_leader_entries(&bytes[5..LEADER_LENGTH], record_length)
+}
+
+// Parses the 19 bytes of the leader after the record length
+pub(crate) fn parse_leader_entries(bytes: &[u8], record_length: usize) -> Result<Leader> {
+    if bytes.len() < LEADER_LENGTH - 5 {
+        return Err(ParseError::TooShort);
+    }
+    let entry_map = EntryMap {
+        length_size: parse_number(&bytes[15..16])?,
+        position_size: parse_number(&bytes[16..17])?,
+        tag_size: parse_number(&bytes[18..19])?,
+    };
+    // The sizes are the widths of the directory entries, which can not be empty
+    if entry_map.length_size == 0 || entry_map.position_size == 0 || entry_map.tag_size == 0 {
+        return Err(ParseError::InvalidEntryMap);
+    }
+    Ok(Leader {
+        record_length,
+        interchange_level: bytes[0],
+        leader_identifier: bytes[1],
+        extension_indicator: bytes[2],
+        version: bytes[3],
+        application_indicator: bytes[4],
+        field_control_length: [bytes[5], bytes[6]],
+        base_address: parse_number(&bytes[7..12])?,
+        character_set: [bytes[12], bytes[13], bytes[14]],
+        entry_map,
+        reserved: bytes[17],
+    })
+}
+
+/// Parses the directory, without the field terminator, into entries. Returns the number of
+/// entries written to the start of `entries`.
+pub fn parse_directory<'a>(
+    bytes: &'a [u8],
+    entry_map: &EntryMap,
+    entries: &mut [DirectoryEntry<'a>],
+) -> Result<usize> {
+    let count = directory_len(bytes, entry_map)?;
+    if count > entries.len() {
+        return Err(ParseError::BufferTooSmall);
+    }
+    let chunks = bytes.chunks_exact(entry_map.entry_size());
+    for (entry, d) in entries.iter_mut().zip(chunks) {
+        let (tag, rest) = d.split_at(entry_map.tag_size);
+        let (length, position) = rest.split_at(entry_map.length_size);
+        *entry = DirectoryEntry {
+            tag,
+            length: parse_number(length)?,
+            position: parse_number(position)?,
+        };
+    }
+    Ok(count)
+}
+
+/// The number of entries of the directory, without the field terminator.
+pub fn directory_len(bytes: &[u8], entry_map: &EntryMap) -> Result<usize> {
+    let entry_size = entry_map.entry_size();
+    if entry_size == 0 || !bytes.len().is_multiple_of(entry_size) {
+        return Err(ParseError::MisalignedDirectory);
+    }
+    Ok(bytes.len() / entry_size)
+}
+
+/// Parses the field controls, the first bytes of a field of the DDR as given by the field
+/// control length of the leader. Controls after the truncated escape sequence are ignored.
+pub fn parse_field_controls(bytes: &[u8]) -> Result<FieldControls<'_>> {
+    if bytes.len() < 6 {
+        return Err(ParseError::TooShort);
+    }
+    Ok(FieldControls {
+        data_structure: bytes[0],
+        data_type: bytes[1],
+        auxiliary: &bytes[2..4],
+        printable_graphics: &bytes[4..6],
+        escape_sequence: &bytes[6..bytes.len().min(9)],
+    })
+}
+
+/// Parses the parenthesied format controls of a field, e.g. `(A(2),I(10),2(R(4)))`, into the
+/// format controls of the outer list. Returns the number written to the start of `controls`.
+pub fn parse_format_controls<'a>(bytes: &'a [u8], controls: &mut [&'a [u8]]) -> Result<usize> {
+    if bytes.len() < 2 || bytes[0] != b'(' || bytes[bytes.len() - 1] != b')' {
+        return Err(ParseError::InvalidFormatControls);
+    }
+    split_format_controls(&bytes[1..bytes.len() - 1], controls)
+}
+
+/// Splits a list of format controls on the commas that are not inside parenthesies. Returns the
+/// number of format controls written to the start of `controls`.
+pub fn split_format_controls<'a>(bytes: &'a [u8], controls: &mut [&'a [u8]]) -> Result<usize> {
+    let mut count = 0;
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut push = |control: &'a [u8]| {
+        let slot = controls.get_mut(count).ok_or(ParseError::BufferTooSmall)?;
+        *slot = control;
+        count += 1;
+        Ok(())
+    };
+    for (i, &b) in bytes.iter().enumerate() {
+        match b {
+            b'(' => depth += 1,
+            b')' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or(ParseError::InvalidFormatControls)?
+            }
+            b',' if depth == 0 => {
+                push(&bytes[start..i])?;
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    if depth != 0 {
+        return Err(ParseError::InvalidFormatControls);
+    }
+    push(&bytes[start..])?;
+    Ok(count)
+}
@@ -1,4 +1,25 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "tokio")]
+pub mod async_catalog;
+#[cfg(feature = "std")]
 pub mod catalog;
+#[cfg(feature = "std")]
+pub mod cell;
+#[cfg(feature = "std")]
 pub mod error;
+pub mod iso8211;
+#[cfg(feature = "csv")]
+pub mod to_csv;
+#[cfg(feature = "geojson")]
+pub mod to_geojson;
+#[cfg(feature = "wkt")]
+pub mod to_wkt;
+#[cfg(feature = "std")]
+pub mod update;
 
+#[cfg(feature = "std")]
 mod data_parser;
+
+#[cfg(feature = "std")]
+pub use catalog::{DataStructureCode, DataTypeCode, TruncEscSeq};
@@ -0,0 +1,77 @@
+//! The to_csv.rs exports the `CATD` records of a catalog as CSV, e.g. for a spreadsheet. The header
+//! row holds the array descriptors of `CATD` as declared in the DDR and every record becomes a row
+//! of the displayed subfield values. Empty and binary subfields are empty cells. Cells are quoted
+//! as described in RFC 4180 when needed and rows end with CRLF.
+use crate::catalog::{Catalog, Data, Record, Result};
+use crate::error::ErrorKind;
+use failure::ResultExt;
+use std::io::{Read, Write};
+
+const CATD: &str = "CATD";
+
+pub fn catalog_to_csv<R: Read, W: Write>(cat: Catalog<R>, mut out: W) -> Result<()> {
+    let header = cat
+        .fields()
+        .find(|(tag, _)| *tag == CATD)
+        .map(|(_, catd)| {
+            catd.array_descriptors()
+                .map(String::from)
+                .collect::<Vec<String>>()
+        })
+        .ok_or_else(|| ErrorKind::InvalidCatalogProfile {
+            missing: vec![CATD.to_string()],
+            unexpected: Vec::new(),
+        })?;
+    write_row(&mut out, &header)?;
+    for record in cat {
+        write_row(&mut out, &row(&record?, &header))?;
+    }
+    out.flush()
+        .with_context(|err| ErrorKind::IOError(err.kind()))?;
+    Ok(())
+}
+
+fn row(record: &Record, header: &[String]) -> Vec<String> {
+    header
+        .iter()
+        .map(
+            |name| match record.get(CATD).and_then(|catd| catd.get(name)) {
+                Some(Data::Binary(_)) | None => String::new(),
+                Some(data) => data.to_string(),
+            },
+        )
+        .collect()
+}
+
+fn write_row<W: Write>(out: &mut W, cells: &[String]) -> Result<()> {
+    let line = cells
+        .iter()
+        .map(|cell| quote(cell))
+        .collect::<Vec<String>>()
+        .join(",");
+    write!(out, "{}\r\n", line).with_context(|err| ErrorKind::IOError(err.kind()))?;
+    Ok(())
+}
+
+// Quotes a cell holding a separator, a quote or a line break, doubling the quotes inside it
+fn quote(cell: &str) -> String {
+    if cell.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("CATALOG.031"), "CATALOG.031");
+        assert_eq!(quote(""), "");
+        assert_eq!(quote("a,b"), "\"a,b\"");
+        assert_eq!(quote("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(quote("two\nlines"), "\"two\nlines\"");
+    }
+}
@@ -0,0 +1,69 @@
+//! The to_geojson.rs exports the records of a catalog as a GeoJSON `FeatureCollection`. Every
+//! `CATD` record becomes a `Feature` with the extent (SLAT, WLON, NLAT, ELON) as a polygon and
+//! FILE, VOLM, IMPL and CRCS as properties. Records without an extent, e.g. text files, get a null
+//! geometry. The JSON is written by hand to keep the crate free of dependencies.
+use crate::catalog::{record_bbox, Catalog, Record, Result};
+use std::fmt::Write;
+use std::io::Read;
+
+const PROPERTIES: [&str; 4] = ["FILE", "VOLM", "IMPL", "CRCS"];
+
+pub fn catalog_to_geojson<R: Read>(cat: Catalog<R>) -> Result<String> {
+    let features = cat
+        .map(|record| Ok(feature(&record?)))
+        .collect::<Result<Vec<String>>>()?;
+    Ok(format!(
+        "{{\"type\":\"FeatureCollection\",\"features\":[\n{}\n]}}\n",
+        features.join(",\n")
+    ))
+}
+
+fn feature(record: &Record) -> String {
+    let geometry = match record_bbox(record) {
+        Some((south, west, north, east)) => format!(
+            "{{\"type\":\"Polygon\",\"coordinates\":[[[{w},{s}],[{e},{s}],[{e},{n}],[{w},{n}],[{w},{s}]]]}}",
+            s = south,
+            w = west,
+            n = north,
+            e = east
+        ),
+        None => String::from("null"),
+    };
+    let properties = PROPERTIES
+        .iter()
+        .map(|&name| {
+            // Empty variable length subfields are written as null
+            let value = record
+                .get_str("CATD", name)
+                .filter(|value| !value.is_empty())
+                .map_or(String::from("null"), quote);
+            format!("{}:{}", quote(name), value)
+        })
+        .collect::<Vec<String>>()
+        .join(",");
+    format!(
+        "{{\"type\":\"Feature\",\"geometry\":{},\"properties\":{{{}}}}}",
+        geometry, properties
+    )
+}
+
+// Quotes and escapes a string as a JSON string
+fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                write!(quoted, "\\u{:04x}", c as u32).unwrap();
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
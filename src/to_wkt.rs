@@ -0,0 +1,54 @@
+//! The to_wkt.rs writes the geometry of features as Well-Known Text, e.g. `POINT (-10.25 59.5)`.
+//! The coordinates are in degrees as (longitude, latitude), i.e. scaled by the COMF, see
+//! `catalog::apply_comf`. Like to_geojson.rs the text is written by hand.
+use std::fmt::Write;
+
+/// The geometry of a feature, assembled from its vector records.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Geometry {
+    Point([f64; 2]),
+    Line(Vec<[f64; 2]>),
+    /// The exterior ring followed by the holes. A ring need not repeat its first coordinate.
+    Polygon(Vec<Vec<[f64; 2]>>),
+}
+
+/// Writes the geometry as a `POINT`, `LINESTRING` or `POLYGON`, the rings of a polygon are closed.
+pub fn feature_to_wkt(geom: &Geometry) -> String {
+    match geom {
+        Geometry::Point([x, y]) => format!("POINT ({} {})", x, y),
+        Geometry::Line(coords) if coords.is_empty() => String::from("LINESTRING EMPTY"),
+        Geometry::Line(coords) => format!("LINESTRING {}", coordinates(coords)),
+        Geometry::Polygon(rings) if rings.is_empty() => String::from("POLYGON EMPTY"),
+        Geometry::Polygon(rings) => {
+            let rings = rings
+                .iter()
+                .map(|ring| coordinates(&close(ring)))
+                .collect::<Vec<String>>();
+            format!("POLYGON ({})", rings.join(", "))
+        }
+    }
+}
+
+// Repeats the first coordinate at the end of the ring unless it is already there
+fn close(ring: &[[f64; 2]]) -> Vec<[f64; 2]> {
+    let mut closed = ring.to_vec();
+    if let (Some(&first), Some(&last)) = (ring.first(), ring.last()) {
+        if first != last {
+            closed.push(first);
+        }
+    }
+    closed
+}
+
+// Writes the coordinates as a parenthesied list, e.g. (1 2, 3 4)
+fn coordinates(coords: &[[f64; 2]]) -> String {
+    let mut text = String::from("(");
+    for (i, [x, y]) in coords.iter().enumerate() {
+        if i > 0 {
+            text.push_str(", ");
+        }
+        write!(text, "{} {}", x, y).unwrap();
+    }
+    text.push(')');
+    text
+}
@@ -0,0 +1,488 @@
+//! The update.rs applies S-57 update files, i.e. the `*.001`, `*.002`, ... data sets that follow a
+//! base cell `*.000`. Every feature (`FRID`) and vector (`VRID`) record of an update carries a
+//! Record Update Instruction `RUIN` telling whether the record is inserted, deleted or modified
+//! in the base cell. Records are matched by their record name `RCNM` and record id `RCID`.
+use crate::catalog::{Data, Field, Record, Result, Warning};
+use crate::cell::Cell;
+use crate::error::ErrorKind;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::io::Read;
+
+const DSID: &str = "DSID";
+const EDTN: &str = "EDTN";
+const UPDN: &str = "UPDN";
+const RUIN: &str = "RUIN";
+
+// The fields which identify feature and vector records
+const IDENTIFIERS: [&str; 2] = ["FRID", "VRID"];
+
+// The attribute fields, updated per attribute label ATTL
+const ATTRIBUTES: [&str; 2] = ["ATTF", "NATF"];
+const ATTL: &str = "ATTL";
+const ATVL: &str = "ATVL";
+// An ATVL of only the delete character removes the attribute
+const DELETE: &str = "\u{7f}";
+
+// An update control field with the subfields holding its instruction, the index of the first
+// repetition to change and the number of repetitions, and the fields it controls
+struct UpdateControl {
+    field: &'static str,
+    instruction: &'static str,
+    index: &'static str,
+    count: &'static str,
+    targets: &'static [&'static str],
+}
+
+const UPDATE_CONTROLS: [UpdateControl; 4] = [
+    UpdateControl {
+        field: "FFPC",
+        instruction: "FFUI",
+        index: "FFIX",
+        count: "NFPT",
+        targets: &["FFPT"],
+    },
+    UpdateControl {
+        field: "FSPC",
+        instruction: "FSUI",
+        index: "FSIX",
+        count: "NSPT",
+        targets: &["FSPT"],
+    },
+    UpdateControl {
+        field: "VRPC",
+        instruction: "VPUI",
+        index: "VPIX",
+        count: "NVPT",
+        targets: &["VRPT"],
+    },
+    UpdateControl {
+        field: "SGCC",
+        instruction: "CCUI",
+        index: "CCIX",
+        count: "CCNC",
+        targets: &["SG2D", "SG3D"],
+    },
+];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Instruction {
+    Insert,
+    Delete,
+    Modify,
+}
+
+impl Instruction {
+    // The update controls use the same codes as RUIN
+    fn from_ruin(ruin: i64) -> Result<Instruction> {
+        match ruin {
+            1 => Ok(Instruction::Insert),
+            2 => Ok(Instruction::Delete),
+            3 => Ok(Instruction::Modify),
+            _ => Err(ErrorKind::BadUpdateInstruction(ruin).into()),
+        }
+    }
+}
+
+// The identifying field of a feature or vector record together with its (RCNM, RCID)
+fn record_key(record: &Record) -> Option<(&'static str, (i64, i64))> {
+    IDENTIFIERS.iter().find_map(|&tag| {
+        let rcnm = record.get_i64(tag, "RCNM")?;
+        let rcid = record.get_i64(tag, "RCID")?;
+        Some((tag, (rcnm, rcid)))
+    })
+}
+
+// The repetitions of a repeating field
+fn elements_mut(field: &mut Field) -> Option<&mut Vec<Field>> {
+    field.values_mut().find_map(|data| match data {
+        Data::Array(elements) => Some(elements),
+        _ => None,
+    })
+}
+
+fn elements(field: &Field) -> Option<(&String, &Vec<Field>)> {
+    field.iter().find_map(|(key, data)| match data {
+        Data::Array(elements) => Some((key, elements)),
+        _ => None,
+    })
+}
+
+impl Record {
+    /// Merges a modifying update record onto this base record.
+    ///
+    /// The subfields of the `FRID` or `VRID` field are overlaid, except the update instruction
+    /// `RUIN`. The attributes of `ATTF` and `NATF` are merged per attribute label, an `ATVL` of only
+    /// the delete character (0x7F) removes the attribute. The repetitions of `FFPT`, `FSPT`, `VRPT`,
+    /// `SG2D` and `SG3D` are inserted, deleted or modified as told by the update control fields
+    /// `FFPC`, `FSPC`, `VRPC` and `SGCC`, without a control field the whole field is replaced. All
+    /// other fields replace the same fields of the base record.
+    pub fn merge(&mut self, update: &Record) -> Result<()> {
+        for control in UPDATE_CONTROLS.iter() {
+            if let Some(control_field) = update.get(control.field) {
+                self.apply_update_control(update, control, control_field)?;
+            }
+        }
+        for (tag, field) in update.iter_fields() {
+            let controlled = UPDATE_CONTROLS.iter().any(|control| {
+                control.field == tag
+                    || (control.targets.contains(&tag) && update.get(control.field).is_some())
+            });
+            if tag == "0001" || controlled {
+                continue;
+            }
+            match self.get_mut(tag) {
+                Some(target) if IDENTIFIERS.contains(&tag) => {
+                    for (name, data) in field.iter().filter(|(name, _)| *name != RUIN) {
+                        target.insert(name.clone(), data.clone());
+                    }
+                }
+                Some(target) if ATTRIBUTES.contains(&tag) => merge_attributes(target, field),
+                _ => self.insert(tag.to_string(), field.clone()),
+            }
+        }
+        Ok(())
+    }
+
+    // Changes the repetitions of the field controlled by the update control field
+    fn apply_update_control(
+        &mut self,
+        update: &Record,
+        control: &UpdateControl,
+        control_field: &Field,
+    ) -> Result<()> {
+        let bad_control = || ErrorKind::BadUpdateControl(control.field.to_string());
+        let subfield = |name: &str| match control_field.get(name) {
+            Some(Data::Integer(Some(value))) => Ok(*value),
+            _ => Err(bad_control()),
+        };
+        let instruction = Instruction::from_ruin(subfield(control.instruction)?)?;
+        let index = usize::try_from(subfield(control.index)?).map_err(|_| bad_control())?;
+        let count = usize::try_from(subfield(control.count)?).map_err(|_| bad_control())?;
+
+        // A deletion carries no repetitions, so the field is looked up in the base record as well
+        let tag = control
+            .targets
+            .iter()
+            .find(|&&tag| update.get(tag).is_some() || self.get(tag).is_some())
+            .ok_or_else(bad_control)?;
+        let changes = update.get(tag).and_then(elements);
+        if self.get(tag).is_none() {
+            let (key, _) = changes.ok_or_else(bad_control)?;
+            let mut field = Field::new();
+            field.insert(key.clone(), Data::Array(Vec::new()));
+            self.insert(tag.to_string(), field);
+        }
+        let target = self
+            .get_mut(tag)
+            .and_then(elements_mut)
+            .ok_or_else(bad_control)?;
+
+        // The index counts the repetitions from 1
+        let start = index
+            .checked_sub(1)
+            .filter(|&start| start <= target.len())
+            .ok_or_else(bad_control)?;
+        let end = match instruction {
+            Instruction::Insert => start,
+            _ => start + count,
+        };
+        if end > target.len() {
+            return Err(bad_control().into());
+        }
+        let replacement = match instruction {
+            Instruction::Delete => Vec::new(),
+            _ => {
+                let (_, changes) = changes.ok_or_else(bad_control)?;
+                if changes.len() < count {
+                    return Err(bad_control().into());
+                }
+                changes[..count].to_vec()
+            }
+        };
+        target.splice(start..end, replacement);
+        Ok(())
+    }
+}
+
+// Merges the attributes of an update ATTF or NATF into the attributes of the base record
+fn merge_attributes(target: &mut Field, update: &Field) {
+    let (key, changes) = match elements(update) {
+        Some(elements) => elements,
+        None => return,
+    };
+    if elements_mut(target).is_none() {
+        target.insert(key.clone(), Data::Array(Vec::new()));
+    }
+    let attributes = match elements_mut(target) {
+        Some(attributes) => attributes,
+        None => return,
+    };
+    for change in changes {
+        let label = change.get(ATTL);
+        let pos = attributes.iter().position(|a| a.get(ATTL) == label);
+        match (change.get(ATVL), pos) {
+            (Some(Data::String(value)), Some(pos)) if value == DELETE => {
+                attributes.remove(pos);
+            }
+            (Some(Data::String(value)), None) if value == DELETE => {}
+            (_, Some(pos)) => attributes[pos] = change.clone(),
+            (_, None) => attributes.push(change.clone()),
+        }
+    }
+}
+
+fn dsid_version(record: &Record) -> Option<(&str, &str)> {
+    Some((record.get_str(DSID, EDTN)?, record.get_str(DSID, UPDN)?))
+}
+
+/// Applies an update file to the records of its base cell, which must already hold all previous
+/// updates. The update number `UPDN` of the update has to follow the one of the base cell and the
+/// edition `EDTN` has to match, otherwise the update is rejected. On success the `DSID` record of
+/// the base cell takes the version of the update.
+///
+/// Inserted records are appended, deleted records are removed and modifying records are merged
+/// onto their base records, see `Record::merge`.
+///
+/// All instructions are checked before the first one is applied, so `base` is left untouched when
+/// an update is rejected. Returns the nonfatal deviations found while reading the update, see
+/// `Cell::warnings`.
+pub fn apply_update<R: Read>(base: &mut Vec<Record>, mut update: Cell<R>) -> Result<Vec<Warning>> {
+    let mut records = update.records().collect::<Result<Vec<Record>>>()?;
+    let warnings = update.warnings().to_vec();
+
+    let dsid_pos = records
+        .iter()
+        .position(|r| r.get(DSID).is_some())
+        .ok_or(ErrorKind::MissingDsid)?;
+    let dsid = records.remove(dsid_pos);
+    let (edition, number) = dsid_version(&dsid).ok_or(ErrorKind::MissingDsid)?;
+
+    let base_dsid = base
+        .iter()
+        .position(|r| r.get(DSID).is_some())
+        .ok_or(ErrorKind::MissingDsid)?;
+    let (base_edition, base_number) =
+        dsid_version(&base[base_dsid]).ok_or(ErrorKind::MissingDsid)?;
+    if edition != base_edition {
+        return Err(ErrorKind::UpdateEditionMismatch {
+            expected: base_edition.to_string(),
+            found: edition.to_string(),
+        }
+        .into());
+    }
+    let expected = base_number
+        .trim()
+        .parse::<u32>()
+        .map(|n| (n + 1).to_string())
+        .map_err(|e| ErrorKind::ParseIntError(e, base_number.to_string()))?;
+    if number.trim() != expected {
+        return Err(ErrorKind::UpdateOutOfOrder {
+            expected,
+            found: number.to_string(),
+        }
+        .into());
+    }
+
+    // Check every instruction against the set of records it will see when it is applied
+    let mut instructions = Vec::with_capacity(records.len());
+    let mut keys = base
+        .iter()
+        .filter_map(record_key)
+        .map(|(_, key)| key)
+        .collect::<HashSet<_>>();
+    for record in records {
+        let (tag, key) = match record_key(&record) {
+            Some(key) => key,
+            None => continue,
+        };
+        let ruin = record.get_i64(tag, RUIN).unwrap_or_default();
+        let instruction = Instruction::from_ruin(ruin)?;
+        let known = match instruction {
+            Instruction::Insert => !keys.insert(key),
+            Instruction::Delete => keys.remove(&key),
+            Instruction::Modify => keys.contains(&key),
+        };
+        match (instruction, known) {
+            (Instruction::Insert, true) => {
+                return Err(ErrorKind::UpdateTargetExists(key.0, key.1).into());
+            }
+            (Instruction::Delete, false) | (Instruction::Modify, false) => {
+                return Err(ErrorKind::UpdateTargetMissing(key.0, key.1).into());
+            }
+            _ => {}
+        }
+        instructions.push((instruction, key, record));
+    }
+
+    let mut updated = base.clone();
+    for (instruction, key, mut record) in instructions {
+        let pos = updated
+            .iter()
+            .position(|r| record_key(r).map(|(_, k)| k) == Some(key));
+        match (instruction, pos) {
+            (Instruction::Insert, _) => {
+                if let Some((tag, _)) = record_key(&record) {
+                    if let Some(id) = record.get_mut(tag) {
+                        id.insert(RUIN.to_string(), Data::Integer(Some(1)));
+                    }
+                }
+                updated.push(record);
+            }
+            (Instruction::Delete, Some(pos)) => {
+                updated.remove(pos);
+            }
+            (Instruction::Modify, Some(pos)) => updated[pos].merge(&record)?,
+            // The check above guarantees the target of a deletion or modification exists
+            _ => unreachable!(),
+        }
+    }
+
+    let base_dsid = updated
+        .iter_mut()
+        .find(|r| r.get(DSID).is_some())
+        .and_then(|r| r.get_mut(DSID))
+        .ok_or(ErrorKind::MissingDsid)?;
+    for subfield in &[UPDN, "UADT", "ISDT"] {
+        if let Some(data) = dsid.get(DSID).and_then(|f| f.get(*subfield)) {
+            base_dsid.insert(subfield.to_string(), data.clone());
+        }
+    }
+    *base = updated;
+    Ok(warnings)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn field(subfields: &[(&str, Data)]) -> Field {
+        subfields
+            .iter()
+            .map(|(name, data)| (name.to_string(), data.clone()))
+            .collect()
+    }
+
+    fn int(value: i64) -> Data {
+        Data::Integer(Some(value))
+    }
+
+    fn attribute(label: i64, value: &str) -> Field {
+        field(&[
+            ("ATTL", int(label)),
+            ("ATVL", Data::String(value.to_string())),
+        ])
+    }
+
+    fn pointer(rcid: u8) -> Field {
+        field(&[
+            ("NAME", Data::Binary(vec![100, rcid, 0, 0, 0])),
+            ("RIND", int(1)),
+        ])
+    }
+
+    fn repeating(key: &str, elements: Vec<Field>) -> Field {
+        field(&[(key, Data::Array(elements))])
+    }
+
+    #[test]
+    fn test_merge_modify_record() {
+        let mut base = Record::default();
+        base.insert(
+            "FRID".to_string(),
+            field(&[
+                ("RCID", int(7)),
+                ("OBJL", int(42)),
+                ("RVER", int(1)),
+                (RUIN, int(1)),
+            ]),
+        );
+        base.insert(
+            "ATTF".to_string(),
+            repeating(
+                "ATTL!ATVL",
+                vec![
+                    attribute(116, "Sandvika"),
+                    attribute(87, "5"),
+                    attribute(75, "3"),
+                ],
+            ),
+        );
+        base.insert(
+            "FSPT".to_string(),
+            repeating("NAME!RIND", vec![pointer(1), pointer(2), pointer(3)]),
+        );
+        base.insert("FOID".to_string(), field(&[("FIDN", int(1))]));
+
+        let mut update = Record::default();
+        update.insert(
+            "FRID".to_string(),
+            field(&[("RCID", int(7)), ("RVER", int(2)), (RUIN, int(3))]),
+        );
+        // Changes DRVAL1, deletes COLOUR and adds INFORM
+        update.insert(
+            "ATTF".to_string(),
+            repeating(
+                "ATTL!ATVL",
+                vec![
+                    attribute(87, "10"),
+                    attribute(75, DELETE),
+                    attribute(102, "Buoy"),
+                ],
+            ),
+        );
+        // Replaces the second pointer
+        update.insert(
+            "FSPC".to_string(),
+            field(&[("FSUI", int(3)), ("FSIX", int(2)), ("NSPT", int(1))]),
+        );
+        update.insert("FSPT".to_string(), repeating("NAME!RIND", vec![pointer(9)]));
+        base.merge(&update).unwrap();
+
+        assert_eq!(
+            base.field_tags().collect::<Vec<_>>(),
+            vec!["FRID", "ATTF", "FSPT", "FOID"]
+        );
+        assert_eq!(base.get_i64("FRID", "OBJL"), Some(42));
+        assert_eq!(base.get_i64("FRID", "RVER"), Some(2));
+        assert_eq!(base.get_i64("FRID", RUIN), Some(1));
+        assert_eq!(
+            base.get_array("ATTF").unwrap(),
+            &[
+                attribute(116, "Sandvika"),
+                attribute(87, "10"),
+                attribute(102, "Buoy")
+            ][..]
+        );
+        assert_eq!(
+            base.get_array("FSPT").unwrap(),
+            &[pointer(1), pointer(9), pointer(3)][..]
+        );
+        assert_eq!(base.get_i64("FOID", "FIDN"), Some(1));
+
+        // Deletes the first two pointers, an update of a deletion carries no FSPT
+        let mut update = Record::default();
+        update.insert(
+            "FSPC".to_string(),
+            field(&[("FSUI", int(2)), ("FSIX", int(1)), ("NSPT", int(2))]),
+        );
+        base.merge(&update).unwrap();
+        assert_eq!(base.get_array("FSPT").unwrap(), &[pointer(3)][..]);
+
+        // Past the end of the pointers
+        let mut update = Record::default();
+        update.insert(
+            "FSPC".to_string(),
+            field(&[("FSUI", int(2)), ("FSIX", int(2)), ("NSPT", int(1))]),
+        );
+        assert!(base.merge(&update).is_err());
+    }
+
+    #[test]
+    fn test_instruction_from_ruin() {
+        assert_eq!(Instruction::from_ruin(1).unwrap(), Instruction::Insert);
+        assert_eq!(Instruction::from_ruin(2).unwrap(), Instruction::Delete);
+        assert_eq!(Instruction::from_ruin(3).unwrap(), Instruction::Modify);
+        assert!(Instruction::from_ruin(4).is_err());
+    }
+}
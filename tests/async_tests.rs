@@ -0,0 +1,79 @@
+#![cfg(feature = "tokio")]
+use futures_util::StreamExt;
+use rust_s57::async_catalog::AsyncCatalog;
+use rust_s57::catalog::{Catalog, Record, Result};
+use rust_s57::error::ErrorKind;
+use std::fs::File;
+
+#[tokio::test]
+async fn test_async_catalog_records() {
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let catalog = AsyncCatalog::new(&bytes[..]).await.unwrap();
+    let records = catalog
+        .records()
+        .collect::<Vec<Result<Record>>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<Record>>>()
+        .unwrap();
+    let expected = Catalog::new(File::open("tests/CATALOG.031").unwrap())
+        .unwrap()
+        .collect::<Result<Vec<Record>>>()
+        .unwrap();
+    assert_eq!(records.len(), expected.len());
+    for (record, expected) in records.iter().zip(expected.iter()) {
+        assert_eq!(record.to_map(), expected.to_map());
+        assert_eq!(record.source_span(), expected.source_span());
+    }
+}
+
+#[tokio::test]
+async fn test_async_catalog_truncated_record() {
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let mut catalog = AsyncCatalog::new(&bytes[..bytes.len() - 10]).await.unwrap();
+    for _ in 0..3 {
+        assert!(catalog.next_record().await.unwrap().is_ok());
+    }
+    let err = catalog.next_record().await.unwrap().unwrap_err();
+    match err.kind() {
+        ErrorKind::TruncatedRecord { expected, got } => {
+            assert_eq!(*expected, 100);
+            assert_eq!(*got, 90);
+        }
+        kind => panic!("Unexpected error: {}", kind),
+    }
+}
+
+#[tokio::test]
+async fn test_async_catalog_padding() {
+    use rust_s57::catalog::Warning;
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let length = bytes.len() as u64;
+    bytes.extend_from_slice(&[b' '; 20]);
+    let mut catalog = AsyncCatalog::new(&bytes[..]).await.unwrap();
+    for _ in 0..4 {
+        assert!(catalog.next_record().await.unwrap().is_ok());
+    }
+    assert!(catalog.next_record().await.is_none());
+    assert!(catalog.next_record().await.is_none());
+    assert_eq!(
+        catalog.warnings(),
+        &[Warning::UnexpectedPadding {
+            offset: length,
+            length: 20
+        }]
+    );
+    bytes.extend_from_slice(b"0");
+    let mut catalog = AsyncCatalog::new(&bytes[..]).await.unwrap();
+    for _ in 0..4 {
+        assert!(catalog.next_record().await.unwrap().is_ok());
+    }
+    let err = catalog.next_record().await.unwrap().unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::InvalidLeader));
+}
+
+#[tokio::test]
+async fn test_async_catalog_empty_reader() {
+    let err = AsyncCatalog::new(&b""[..]).await.unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::CouldNotParseCatalog));
+}
@@ -1,5 +1,10 @@
-use rust_s57::catalog::Catalog;
+#![cfg(feature = "std")]
+use rust_s57::catalog::{Catalog, InterchangeLevel};
+use rust_s57::error::ErrorKind;
+use std::cell::Cell;
 use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::rc::Rc;
 
 type Result<T> = std::result::Result<T, failure::Error>;
 
@@ -36,3 +41,1312 @@ fn try_main() -> Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn test_catalog_fields() {
+    let cf = File::open("tests/CATALOG.031").unwrap();
+    let catalog = Catalog::new(cf).unwrap();
+    let (_, catd) = catalog.fields().find(|(tag, _)| *tag == "CATD").unwrap();
+    assert_eq!(catd.name(), "Catalogue Directory Field");
+    let subfields = catd
+        .subfields()
+        .map(|(label, fc)| format!("{}={}", label, fc))
+        .collect::<Vec<String>>();
+    assert_eq!(subfields[0], "RCNM=A(2)");
+    assert_eq!(subfields[1], "RCID=I(10)");
+    assert_eq!(subfields[2], "FILE=A");
+    assert_eq!(subfields.len(), 12);
+    let widths = catd
+        .format_controls()
+        .map(|fc| fc.byte_width())
+        .collect::<Vec<Option<usize>>>();
+    assert_eq!(&widths[..3], &[Some(2), Some(10), None]);
+}
+
+#[test]
+fn test_catalog_field_schema_clone() {
+    let cf = File::open("tests/CATALOG.031").unwrap();
+    let catalog = Catalog::new(cf).unwrap();
+    let (_, catd) = catalog.fields().find(|(tag, _)| *tag == "CATD").unwrap();
+    let copy = catd;
+    assert_eq!(copy, catd);
+    assert_eq!(copy.name(), catd.name());
+    let (_, drid) = catalog.fields().find(|(tag, _)| *tag == "0001").unwrap();
+    assert_ne!(drid, catd);
+}
+
+#[test]
+fn test_parse_ddr_bytes() {
+    use rust_s57::catalog::parse_ddr_bytes;
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let schema = parse_ddr_bytes(&bytes).unwrap();
+    assert_eq!(schema.record_length(), 262);
+    assert_eq!(
+        schema.interchange_level().unwrap(),
+        InterchangeLevel::Level3
+    );
+    assert_eq!(schema.base_address(), 73);
+    let tags = schema
+        .directory()
+        .map(|(tag, _, _)| tag)
+        .collect::<Vec<&str>>();
+    assert_eq!(tags, vec!["0000", "0001", "CATD"]);
+    let catd = schema.field("CATD").unwrap();
+    assert_eq!(catd.name(), "Catalogue Directory Field");
+    assert_eq!(
+        catd.array_descriptors().collect::<Vec<&str>>(),
+        vec![
+            "RCNM", "RCID", "FILE", "LFIL", "VOLM", "IMPL", "SLAT", "WLON", "NLAT", "ELON", "CRCS",
+            "COMT"
+        ]
+    );
+    assert!(schema.field("DSID").is_none());
+    assert_eq!(schema.fields().count(), 2);
+    // Only the DDR is needed
+    assert!(parse_ddr_bytes(&bytes[..262]).is_ok());
+    assert!(parse_ddr_bytes(&bytes[..200]).is_err());
+}
+
+#[test]
+fn test_catalog_field_tag_pairs() {
+    let cf = File::open("tests/CATALOG.031").unwrap();
+    let catalog = Catalog::new(cf).unwrap();
+    let pairs = catalog.field_tag_pairs().collect::<Vec<(&str, &str)>>();
+    assert_eq!(pairs, vec![("0001", "CATD")]);
+}
+
+#[test]
+fn test_catalog_dataset() {
+    use rust_s57::catalog::{Dataset, Record};
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let records = Catalog::new(&bytes[..])
+        .unwrap()
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    let dataset = Catalog::new(&bytes[..])
+        .unwrap()
+        .collect::<rust_s57::catalog::Result<Dataset>>()
+        .unwrap();
+    assert_eq!(dataset.len(), 4);
+    assert!(dataset.unidentified().is_empty());
+    assert_eq!(
+        dataset.iter().collect::<Vec<_>>(),
+        records.iter().collect::<Vec<_>>()
+    );
+    let id = records[2].id().unwrap();
+    assert_eq!(dataset.get(id), Some(&records[2]));
+    assert_eq!(dataset.get(-1), None);
+
+    let dataset = vec![Record::default()].into_iter().collect::<Dataset>();
+    assert!(dataset.is_empty());
+    assert_eq!(dataset.unidentified().len(), 1);
+}
+
+#[test]
+fn test_catalog_map_records() {
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let ids = Catalog::new(&bytes[..])
+        .unwrap()
+        .map_records(|record| record.id())
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(ids, vec![Some(1), Some(2), Some(3), Some(4)]);
+
+    // Errors are passed through
+    let mut bytes = bytes;
+    let rcid = bytes
+        .windows(12)
+        .position(|w| w == b"CD0000000002")
+        .unwrap();
+    bytes[rcid + 7] = b'x';
+    let results = Catalog::new(&bytes[..])
+        .unwrap()
+        .map_records(|record| record.id())
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 4);
+    assert!(results[1].is_err());
+}
+
+#[test]
+fn test_catalog_volumes() {
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    // Move the file of the last record to a second volume
+    let volm = bytes.windows(6).rposition(|w| w == b"V01X01").unwrap();
+    bytes[volm..volm + 6].copy_from_slice(b"V02X02");
+    let mut catalog = Catalog::new(&bytes[..]).unwrap();
+    let first = catalog.next().unwrap().unwrap();
+    assert_eq!(first.volume(), Some("V01X01"));
+    let mut catalog = Catalog::new(&bytes[..]).unwrap();
+    let volumes = catalog.volumes().unwrap();
+    assert_eq!(
+        volumes.iter().map(String::as_str).collect::<Vec<_>>(),
+        vec!["V01X01", "V02X02"]
+    );
+}
+
+#[test]
+fn test_catalog_file_control() {
+    let cf = File::open("tests/CELL.000").unwrap();
+    let catalog = Catalog::new(cf).unwrap();
+    let file_control = catalog.file_control();
+    assert_eq!(file_control.name(), "");
+    assert_eq!(file_control.root(), Some("0001"));
+    assert_eq!(
+        file_control.children("0001").collect::<Vec<_>>(),
+        vec!["DSID", "FRID", "VRID"]
+    );
+    assert_eq!(
+        file_control.children("VRID").collect::<Vec<_>>(),
+        vec!["SG2D"]
+    );
+    assert_eq!(file_control.parent("SG2D"), Some("VRID"));
+    assert_eq!(file_control.parent("0001"), None);
+    assert_eq!(file_control.children("SG2D").count(), 0);
+}
+
+#[test]
+fn test_catalog_within_bbox() {
+    let cf = File::open("tests/BBOX.031").unwrap();
+    let mut catalog = Catalog::new(cf).unwrap();
+    let records = catalog
+        .within_bbox(10.2, 10.2, 12.0, 12.0)
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].get_str("CATD", "FILE"), Some("NO1CELLA.000"));
+}
+
+#[test]
+fn test_catalog_within_bbox_antimeridian() {
+    let cf = File::open("tests/BBOX.031").unwrap();
+    let mut catalog = Catalog::new(cf).unwrap();
+    let records = catalog
+        .within_bbox(50.5, 179.0, 52.0, -179.0)
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].get_str("CATD", "FILE"), Some("NO2CELLB.000"));
+}
+
+#[test]
+fn test_catalog_seek_to_record() {
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let mut catalog = Catalog::new(std::io::Cursor::new(bytes)).unwrap();
+    assert!(catalog.seek_to_record(3).unwrap().is_none());
+    catalog.build_index().unwrap();
+
+    let record = catalog.seek_to_record(3).unwrap().unwrap();
+    assert_eq!(record.get_str("CATD", "FILE"), Some("AAMNPUB1.TXT"));
+    let record = catalog.seek_to_record(2).unwrap().unwrap();
+    assert_eq!(record.get_str("CATD", "FILE"), Some("AA5OTHER.000"));
+    // Iteration continues after the sought record
+    let next = catalog.next().unwrap().unwrap();
+    assert_eq!(next.id(), Some(3));
+    assert!(catalog.seek_to_record(42).unwrap().is_none());
+}
+
+#[test]
+fn test_catalog_build_index_keeps_position() {
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let mut catalog = Catalog::new(std::io::Cursor::new(bytes)).unwrap();
+    let first = catalog.next().unwrap().unwrap();
+    assert_eq!(first.id(), Some(1));
+    catalog.build_index().unwrap();
+    let second = catalog.next().unwrap().unwrap();
+    assert_eq!(second.id(), Some(2));
+}
+
+#[test]
+fn test_catalog_corrupt_subfield() {
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    // Corrupt the RCID of the second record
+    let rcid = bytes
+        .windows(12)
+        .position(|w| w == b"CD0000000002")
+        .unwrap();
+    bytes[rcid + 7] = b'x';
+    let mut catalog = Catalog::new(std::io::Cursor::new(bytes)).unwrap();
+    assert!(catalog.next().unwrap().is_ok());
+    let err = catalog.next().unwrap().unwrap_err();
+    match err.kind() {
+        ErrorKind::FieldParse {
+            field,
+            subfield,
+            offset,
+        } => {
+            assert_eq!(field, "CATD");
+            assert_eq!(subfield, "RCID");
+            assert_eq!(*offset, 61);
+        }
+        kind => panic!("Unexpected error: {}", kind),
+    }
+}
+
+#[test]
+fn test_catalog_unknown_field() {
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    // Point the directory of the first record to a field the DDR does not describe
+    let entry = 262
+        + bytes[262..]
+            .windows(14)
+            .position(|w| w == b"CATD0004200006")
+            .unwrap();
+    bytes[entry..entry + 4].copy_from_slice(b"XXXX");
+    let mut catalog = Catalog::new(&bytes[..]).unwrap();
+    let err = catalog.next().unwrap().unwrap_err();
+    match err.kind() {
+        ErrorKind::UnknownField(tag) => assert_eq!(tag, "XXXX"),
+        kind => panic!("Unexpected error: {}", kind),
+    }
+}
+
+#[test]
+fn test_catalog_project() {
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let files = Catalog::new(&bytes[..])
+        .unwrap()
+        .map(|r| r.unwrap().get_str("CATD", "FILE").unwrap().to_string())
+        .collect::<Vec<_>>();
+    // An RCID that does not parse shows which subfields the projection decodes
+    let rcid = bytes
+        .windows(12)
+        .position(|w| w == b"CD0000000002")
+        .unwrap();
+    bytes[rcid + 7] = b'x';
+    assert!(Catalog::new(&bytes[..]).unwrap().any(|r| r.is_err()));
+
+    let mut catalog = Catalog::new(&bytes[..]).unwrap();
+    let records = catalog
+        .project(&[("CATD", "FILE")])
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        records
+            .iter()
+            .map(|r| r.get_str("CATD", "FILE").unwrap())
+            .collect::<Vec<_>>(),
+        files
+    );
+    assert_eq!(records[0].field_tags().collect::<Vec<_>>(), vec!["CATD"]);
+    assert_eq!(records[0].get("CATD").unwrap().len(), 1);
+}
+
+// A reader that hands out a single byte per read
+struct Dribble<R: Read>(R);
+
+impl<R: Read> Read for Dribble<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = buf.len().min(1);
+        self.0.read(&mut buf[..len])
+    }
+}
+
+#[test]
+fn test_catalog_short_reads() {
+    let cf = File::open("tests/CATALOG.031").unwrap();
+    let catalog = Catalog::new(Dribble(cf)).unwrap();
+    let records = catalog
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(records.len(), 4);
+}
+
+// A reader like a socket, handing out chunks of varying size in between interrupted reads
+struct Socket<R: Read> {
+    rdr: R,
+    calls: usize,
+}
+
+impl<R: Read> Read for Socket<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.calls += 1;
+        match self.calls % 4 {
+            0 | 1 => Err(std::io::ErrorKind::Interrupted.into()),
+            _ => {
+                let len = buf.len().min(self.calls % 7 + 1);
+                self.rdr.read(&mut buf[..len])
+            }
+        }
+    }
+}
+
+#[test]
+fn test_catalog_socket_reads() {
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let expected = Catalog::new(&bytes[..])
+        .unwrap()
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    // Trailing padding is read to the end as well
+    bytes.extend_from_slice(&[b' '; 20]);
+    let socket = Socket {
+        rdr: &bytes[..],
+        calls: 0,
+    };
+    let mut catalog = Catalog::new(socket).unwrap();
+    let records = catalog
+        .by_ref()
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(records, expected);
+    assert_eq!(catalog.warnings().len(), 1);
+}
+
+// A non-blocking reader without data
+struct WouldBlock;
+
+impl Read for WouldBlock {
+    fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+        Err(std::io::ErrorKind::WouldBlock.into())
+    }
+}
+
+#[test]
+fn test_catalog_would_block() {
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let mut catalog = Catalog::new(Read::chain(&bytes[..300], WouldBlock)).unwrap();
+    match catalog.next() {
+        Some(Err(err)) => match err.kind() {
+            ErrorKind::IOError(std::io::ErrorKind::WouldBlock) => {}
+            kind => panic!("unexpected error {}", kind),
+        },
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_catalog_buf_reader() {
+    let cf = File::open("tests/CATALOG.031").unwrap();
+    let catalog = Catalog::new(BufReader::new(cf)).unwrap();
+    let records = catalog
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(records.len(), 4);
+}
+
+#[test]
+fn test_catalog_space_padded_length() {
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    // The first record is 101 bytes long, pad its length with blanks instead of zeros
+    assert_eq!(&bytes[262..267], b"00101");
+    bytes[262..267].copy_from_slice(b"101  ");
+    let records = Catalog::new(&bytes[..])
+        .unwrap()
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(records.len(), 4);
+    assert_eq!(records[0].source_span(), (262, 101));
+}
+
+#[test]
+fn test_catalog_estimated_remaining() {
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let mut catalog = Catalog::new(std::io::Cursor::new(bytes)).unwrap();
+    assert_eq!(catalog.estimated_remaining().unwrap(), None);
+    // The records are 101, 150, 103 and 100 bytes long
+    catalog.next().unwrap().unwrap();
+    assert_eq!(catalog.estimated_remaining().unwrap(), Some(3));
+    catalog.next().unwrap().unwrap();
+    assert_eq!(catalog.estimated_remaining().unwrap(), Some(2));
+    // Estimating does not move the reader
+    assert_eq!(catalog.next().unwrap().unwrap().id(), Some(3));
+    catalog.next().unwrap().unwrap();
+    assert_eq!(catalog.estimated_remaining().unwrap(), Some(0));
+    assert!(catalog.next().is_none());
+}
+
+#[test]
+fn test_catalog_truncated_length() {
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    // The last record is 100 bytes long, leave only three bytes of its length
+    let last = bytes.len() - 100 + 3;
+    bytes.truncate(last);
+    let catalog = Catalog::new(std::io::Cursor::new(bytes)).unwrap();
+    let results = catalog.collect::<Vec<_>>();
+    assert_eq!(results.len(), 4);
+    match results[3].as_ref().unwrap_err().kind() {
+        ErrorKind::TruncatedRecord { expected, got } => {
+            assert_eq!(*expected, 5);
+            assert_eq!(*got, 3);
+        }
+        kind => panic!("Unexpected error: {}", kind),
+    }
+}
+
+#[test]
+fn test_catalog_trailing_padding() {
+    use rust_s57::catalog::Warning;
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let end = bytes.len() as u64;
+    bytes.extend_from_slice(&[b' '; 7]);
+    bytes.extend_from_slice(&[0; 3]);
+    let mut catalog = Catalog::new(&bytes[..]).unwrap();
+    let records = catalog
+        .by_ref()
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(records.len(), 4);
+    assert_eq!(
+        catalog.warnings(),
+        &[Warning::UnexpectedPadding {
+            offset: end,
+            length: 10
+        }]
+    );
+
+    // Padding followed by anything else is not padding
+    bytes.push(b'x');
+    let results = Catalog::new(&bytes[..]).unwrap().collect::<Vec<_>>();
+    assert_eq!(results.len(), 5);
+    assert!(results[4].is_err());
+}
+
+#[test]
+fn test_catalog_non_canonical_field_order() {
+    use rust_s57::catalog::Warning;
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    // Swap the directory entries of the first record, so CATD is listed before 0001
+    let dir = bytes
+        .windows(28)
+        .position(|w| w == b"00010000600000CATD0004200006")
+        .unwrap();
+    bytes[dir..dir + 28].copy_from_slice(b"CATD000420000600010000600000");
+    let mut catalog = Catalog::new(&bytes[..]).unwrap();
+    let record = catalog.next().unwrap().unwrap();
+    assert_eq!(record.id(), Some(1));
+    assert_eq!(
+        catalog.warnings(),
+        &[Warning::NonCanonicalFieldOrder {
+            offset: 262,
+            tag: "CATD".to_string()
+        }]
+    );
+}
+
+#[test]
+fn test_catalog_record_equality() {
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let a = Catalog::new(&bytes[..])
+        .unwrap()
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    let b = Catalog::new(&bytes[..])
+        .unwrap()
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(a, b);
+    assert_ne!(a[0], a[1]);
+
+    // Change the volume of the first record from V01X01 to V01X02
+    let mut tweaked = bytes.clone();
+    let pos = tweaked.windows(6).position(|w| w == b"V01X01").unwrap();
+    tweaked[pos + 5] = b'2';
+    let c = Catalog::new(&tweaked[..])
+        .unwrap()
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    assert_ne!(a[0], c[0]);
+    assert_eq!(a[1..], c[1..]);
+}
+
+#[test]
+fn test_catalog_comment() {
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let records = Catalog::new(&bytes[..])
+        .unwrap()
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(records[3].get_str("CATD", "COMT"), Some(""));
+    assert_eq!(records[3].comment(), None);
+
+    // Shorten the volume of the last record to make room for a comment
+    let old = b"V01X01\x1fTXT\x1f\x1f\x1f\x1f\x1f\x1f\x1e";
+    let new = b"V01X\x1fTXT\x1f\x1f\x1f\x1f\x1fHi\x1f\x1e";
+    let pos = bytes.len() - old.len();
+    assert_eq!(&bytes[pos..], &old[..]);
+    bytes[pos..].copy_from_slice(new);
+    let record = Catalog::new(&bytes[..]).unwrap().nth(3).unwrap().unwrap();
+    assert_eq!(record.comment(), Some("Hi"));
+}
+
+#[test]
+fn test_catalog_no_warnings() {
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let mut catalog = Catalog::new(&bytes[..]).unwrap();
+    assert_eq!(catalog.by_ref().count(), 4);
+    assert!(catalog.warnings().is_empty());
+}
+
+#[test]
+fn test_catalog_truncated_record() {
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    // Cut the last record, which is 100 bytes long, in the middle of its field area
+    bytes.truncate(bytes.len() - 10);
+    let mut catalog = Catalog::new(std::io::Cursor::new(bytes)).unwrap();
+    for _ in 0..3 {
+        assert!(catalog.next().unwrap().is_ok());
+    }
+    match catalog.next().unwrap().unwrap_err().kind() {
+        ErrorKind::TruncatedRecord { expected, got } => {
+            assert_eq!(*expected, 100);
+            assert_eq!(*got, 90);
+        }
+        kind => panic!("Unexpected error: {}", kind),
+    }
+    // The reader ended inside the record, so there is nothing more to read
+    assert!(catalog.next().is_none());
+}
+
+#[test]
+fn test_catalog_directory_out_of_bounds() {
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    // Make the CATD directory entry of the first record claim 999 bytes
+    let dir = bytes
+        .windows(14)
+        .position(|w| w == b"CATD0004200006")
+        .unwrap();
+    bytes[dir + 4..dir + 9].copy_from_slice(b"00999");
+    let mut catalog = Catalog::new(std::io::Cursor::new(bytes)).unwrap();
+    let err = catalog.next().unwrap().unwrap_err();
+    match err.kind() {
+        ErrorKind::DirectoryOutOfBounds(tag) => assert_eq!(tag, "CATD"),
+        kind => panic!("Unexpected error: {}", kind),
+    }
+}
+
+#[test]
+fn test_catalog_reset() {
+    let cf = File::open("tests/CATALOG.031").unwrap();
+    let mut catalog = Catalog::from_seekable(cf).unwrap();
+    let first = catalog
+        .by_ref()
+        .map(|r| r.unwrap().id())
+        .collect::<Vec<_>>();
+    catalog.reset().unwrap();
+    let second = catalog
+        .by_ref()
+        .map(|r| r.unwrap().id())
+        .collect::<Vec<_>>();
+    assert_eq!(first.len(), 4);
+    assert_eq!(first, second);
+}
+
+#[test]
+fn test_catalog_from_seekable_after_prefix() {
+    // The catalog does not have to start at the beginning of the reader
+    let mut bytes = b"PREFIX".to_vec();
+    bytes.extend(std::fs::read("tests/CATALOG.031").unwrap());
+    let mut cur = std::io::Cursor::new(bytes);
+    cur.set_position(6);
+    let mut catalog = Catalog::from_seekable(cur).unwrap();
+    assert_eq!(catalog.by_ref().count(), 4);
+    catalog.reset().unwrap();
+    assert_eq!(catalog.next().unwrap().unwrap().id(), Some(1));
+}
+
+#[test]
+fn test_catalog_leader() {
+    let cf = File::open("tests/CATALOG.031").unwrap();
+    let catalog = Catalog::new(cf).unwrap();
+    assert_eq!(
+        catalog.interchange_level().unwrap(),
+        InterchangeLevel::Level3
+    );
+    assert_eq!(catalog.application_indicator(), ' ');
+}
+
+// A reader that counts the bytes read through it
+struct Counting<R> {
+    inner: R,
+    read: Rc<Cell<usize>>,
+}
+
+impl<R: Read> Read for Counting<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read.set(self.read.get() + n);
+        Ok(n)
+    }
+}
+
+impl<R: Seek> Seek for Counting<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+#[test]
+fn test_catalog_count_records() {
+    let cf = File::open("tests/CATALOG.031").unwrap();
+    let read = Rc::new(Cell::new(0));
+    let mut catalog = Catalog::new(Counting {
+        inner: cf,
+        read: read.clone(),
+    })
+    .unwrap();
+    let after_ddr = read.get();
+    assert_eq!(catalog.count_records().unwrap(), 4);
+    // Only the five length bytes of each record are read
+    assert_eq!(read.get() - after_ddr, 4 * 5);
+    assert_eq!(catalog.count(), 4);
+}
+
+#[test]
+fn test_catalog_count_records_padding() {
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    bytes.extend_from_slice(&[0; 20]);
+    let mut catalog = Catalog::new(std::io::Cursor::new(&bytes[..])).unwrap();
+    catalog.next().unwrap().unwrap();
+    assert_eq!(catalog.count_records().unwrap(), 4);
+    assert_eq!(catalog.count(), 3);
+    bytes.extend_from_slice(b"0");
+    let mut catalog = Catalog::new(std::io::Cursor::new(&bytes[..])).unwrap();
+    assert!(catalog.count_records().is_err());
+}
+
+#[test]
+fn test_catalog_missing_field_terminator() {
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    // Overwrite the terminator of the 0001 field in the first record
+    let rcid = bytes
+        .windows(7)
+        .position(|w| w == b"\x1e00001\x1e")
+        .unwrap();
+    bytes[rcid + 6] = b' ';
+    let mut catalog = Catalog::new(std::io::Cursor::new(bytes)).unwrap();
+    let err = catalog.next().unwrap().unwrap_err();
+    match err.kind() {
+        ErrorKind::MissingFieldTerminator(tag) => assert_eq!(tag, "0001"),
+        kind => panic!("Unexpected error: {}", kind),
+    }
+}
+
+#[test]
+fn test_error_into_boxed_std_error() {
+    fn open() -> std::result::Result<Catalog<&'static [u8]>, Box<dyn std::error::Error>> {
+        Ok(Catalog::new(&b"0012"[..])?)
+    }
+    let err = open().unwrap_err();
+    assert!(!err.to_string().is_empty());
+    let err = err.downcast::<rust_s57::error::Error>().unwrap();
+    match err.kind() {
+        ErrorKind::CouldNotParseCatalog => {}
+        ErrorKind::EOF => panic!("unexpected EOF"),
+        ErrorKind::IOError(kind) => panic!("unexpected io error {:?}", kind),
+        kind => panic!("unexpected error {}", kind),
+    }
+    let source = std::error::Error::source(&*err).unwrap();
+    assert!(source.downcast_ref::<rust_s57::error::Error>().is_some());
+}
+
+#[test]
+fn test_catalog_records_by_field() {
+    let cf = File::open("tests/CELL.000").unwrap();
+    let catalog = Catalog::new(cf).unwrap();
+    let records = catalog
+        .records_by_field("FRID")
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].id(), Some(2));
+    assert_eq!(records[1].id(), Some(3));
+}
+
+#[test]
+fn test_catalog_records_by_field_passes_errors() {
+    let mut bytes = std::fs::read("tests/CELL.000").unwrap();
+    // Overwrite the terminator of the DSID field, which has no FRID
+    let dsnm = bytes
+        .windows(10)
+        .position(|w| w == b"CELL.000\x1f\x1e")
+        .unwrap();
+    bytes[dsnm + 9] = b' ';
+    let catalog = Catalog::new(&bytes[..]).unwrap();
+    let mut records = catalog.records_by_field("FRID");
+    let err = records.next().unwrap().unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::MissingFieldTerminator(_)));
+    assert_eq!(records.next().unwrap().unwrap().id(), Some(2));
+    assert_eq!(records.next().unwrap().unwrap().id(), Some(3));
+    assert!(records.next().is_none());
+}
+
+#[test]
+fn test_catalog_from_path() {
+    let from_path = Catalog::from_path("tests/CATALOG.031")
+        .unwrap()
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    let from_file = Catalog::new(File::open("tests/CATALOG.031").unwrap())
+        .unwrap()
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(from_path.len(), 4);
+    assert_eq!(from_path.len(), from_file.len());
+    for (a, b) in from_path.iter().zip(from_file.iter()) {
+        assert!(a.iter_fields().eq(b.iter_fields()));
+    }
+}
+
+#[test]
+fn test_catalog_from_path_missing_file() {
+    let err = Catalog::from_path("tests/MISSING.031").unwrap_err();
+    match err.kind() {
+        ErrorKind::IOError(kind) => assert_eq!(*kind, std::io::ErrorKind::NotFound),
+        kind => panic!("Unexpected error: {}", kind),
+    }
+}
+
+#[test]
+fn test_catalog_zero_field_tag_size() {
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    // The last byte of the DDR leader is the size of the field tag field
+    bytes[23] = b'0';
+    assert!(Catalog::new(&bytes[..]).is_err());
+}
+
+#[test]
+fn test_catalog_record_name() {
+    use rust_s57::catalog::RecordName;
+    let cf = File::open("tests/CATALOG.031").unwrap();
+    let mut catalog = Catalog::new(cf).unwrap();
+    let record = catalog.next().unwrap().unwrap();
+    assert_eq!(record.record_name(), Some(RecordName::CD));
+}
+
+#[test]
+fn test_catalog_validate() {
+    let cf = File::open("tests/CATALOG.031").unwrap();
+    assert!(Catalog::new(cf).unwrap().validate().is_ok());
+}
+
+#[test]
+fn test_catalog_validate_missing_crcs() {
+    let cf = File::open("tests/NOCRCS.031").unwrap();
+    let err = Catalog::new(cf).unwrap().validate().unwrap_err();
+    match err.kind() {
+        ErrorKind::InvalidCatalogProfile {
+            missing,
+            unexpected,
+        } => {
+            assert_eq!(missing, &vec!["CATD.CRCS".to_string()]);
+            assert!(unexpected.is_empty());
+        }
+        kind => panic!("Unexpected error: {}", kind),
+    }
+    assert!(err.to_string().contains("CATD.CRCS"));
+}
+
+#[test]
+fn test_catalog_validate_cell() {
+    let cf = File::open("tests/CELL.000").unwrap();
+    let err = Catalog::new(cf).unwrap().validate().unwrap_err();
+    match err.kind() {
+        ErrorKind::InvalidCatalogProfile {
+            missing,
+            unexpected,
+        } => {
+            assert_eq!(missing, &vec!["CATD".to_string()]);
+            assert_eq!(unexpected, &vec!["DSID", "FRID", "SG2D", "VRID"]);
+        }
+        kind => panic!("Unexpected error: {}", kind),
+    }
+}
+
+#[test]
+fn test_catalog_large_reuses_buffer() {
+    // Repeat the Data Records, which differ in length, to a catalog of 40000 records
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let (ddr, records) = bytes.split_at(262);
+    let mut large = ddr.to_vec();
+    for _ in 0..10_000 {
+        large.extend_from_slice(records);
+    }
+    let expected = Catalog::new(&bytes[..])
+        .unwrap()
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    let mut count = 0;
+    for (record, expected) in Catalog::new(&large[..])
+        .unwrap()
+        .zip(expected.iter().cycle())
+    {
+        let record = record.unwrap();
+        assert!(record.iter_fields().eq(expected.iter_fields()));
+        count += 1;
+    }
+    assert_eq!(count, 40_000);
+}
+
+#[test]
+fn test_catalog_schema_digest() {
+    use std::convert::TryFrom;
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let a = Catalog::try_from(&bytes[..]).unwrap();
+    let b = Catalog::try_from(&bytes[..]).unwrap();
+    assert_eq!(a.schema_digest(), b.schema_digest());
+
+    // Change the format control of RCID from I(10) to I(11)
+    let mut tweaked = bytes.clone();
+    let pos = tweaked.windows(5).position(|w| w == b"I(10)").unwrap();
+    tweaked[pos + 3] = b'1';
+    let c = Catalog::try_from(&tweaked[..]).unwrap();
+    assert_ne!(a.schema_digest(), c.schema_digest());
+}
+
+#[test]
+fn test_catalog_try_from_bytes() {
+    use std::convert::TryFrom;
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let catalog = Catalog::try_from(&bytes[..]).unwrap();
+    let records = catalog
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(records.len(), 4);
+    assert_eq!(records[0].get_str("CATD", "FILE"), Some("CATALOG.031"));
+    assert!(Catalog::try_from(&bytes[..10]).is_err());
+}
+
+#[test]
+fn test_catalog_implementation() {
+    use rust_s57::catalog::FileImplementation;
+    let cf = File::open("tests/CATALOG.031").unwrap();
+    let implementations = Catalog::new(cf)
+        .unwrap()
+        .map(|r| r.unwrap().implementation())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        implementations,
+        vec![
+            Some(FileImplementation::Ascii),
+            Some(FileImplementation::Binary),
+            Some(FileImplementation::Text),
+            Some(FileImplementation::Text)
+        ]
+    );
+}
+
+#[test]
+fn test_catalog_lenient_corrupt_leader() {
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    // Corrupt the length of the second record
+    let second = bytes
+        .windows(12)
+        .position(|w| w == b"00150 D     ")
+        .unwrap();
+    bytes[second + 3] = b'x';
+    let mut catalog = Catalog::new(&bytes[..]).unwrap().lenient();
+    let ids = catalog.by_ref().map(|r| r.id()).collect::<Vec<_>>();
+    assert_eq!(ids, vec![Some(1), Some(3), Some(4)]);
+    assert_eq!(catalog.errors().len(), 1);
+}
+
+#[test]
+fn test_catalog_lenient_corrupt_subfield() {
+    let mut bytes = std::fs::read("tests/BBOX.031").unwrap();
+    // Corrupt the RCID of the second record
+    let rcid = bytes
+        .windows(12)
+        .position(|w| w == b"CD0000000002")
+        .unwrap();
+    bytes[rcid + 7] = b'x';
+    let mut catalog = Catalog::new(&bytes[..]).unwrap().lenient();
+    let files = catalog
+        .by_ref()
+        .map(|r| r.get_str("CATD", "FILE").map(String::from))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        files,
+        vec![
+            Some("CATALOG.031".to_string()),
+            Some("NO2CELLB.000".to_string())
+        ]
+    );
+    match catalog.errors()[0].kind() {
+        ErrorKind::FieldParse { subfield, .. } => assert_eq!(subfield, "RCID"),
+        kind => panic!("Unexpected error: {}", kind),
+    }
+}
+
+#[test]
+fn test_catalog_lenient_truncated() {
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    bytes.truncate(bytes.len() - 10);
+    let mut catalog = Catalog::new(&bytes[..]).unwrap().lenient();
+    assert_eq!(catalog.by_ref().count(), 3);
+    assert_eq!(catalog.errors().len(), 1);
+}
+
+#[test]
+fn test_catalog_source_span() {
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let spans = Catalog::new(&bytes[..])
+        .unwrap()
+        .map(|r| r.unwrap().source_span())
+        .collect::<Vec<_>>();
+    assert_eq!(spans[0], (262, 101));
+    for pair in spans.windows(2) {
+        assert_eq!(pair[1].0, pair[0].0 + pair[0].1 as u64);
+    }
+    let (offset, length) = spans[spans.len() - 1];
+    assert_eq!(offset as usize + length, bytes.len());
+    // The record starts with its length
+    assert_eq!(&bytes[offset as usize..offset as usize + 5], b"00100");
+}
+
+#[test]
+fn test_catalog_lenient_source_span() {
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let expected = Catalog::new(&bytes[..])
+        .unwrap()
+        .map(|r| r.unwrap().source_span())
+        .collect::<Vec<_>>();
+    let spans = Catalog::new(&bytes[..])
+        .unwrap()
+        .lenient()
+        .map(|r| r.source_span())
+        .collect::<Vec<_>>();
+    assert_eq!(spans, expected);
+}
+
+#[test]
+fn test_catalog_options_lexical_level() {
+    use rust_s57::catalog::{CatalogOptions, LexicalLevel};
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    // Replace the D of README.TXT by the ISO 8859-1 å
+    let readme = bytes.windows(10).position(|w| w == b"README.TXT").unwrap();
+    bytes[readme + 3] = 0xE5;
+
+    let results = Catalog::new(&bytes[..]).unwrap().collect::<Vec<_>>();
+    assert!(results[3].is_err());
+
+    let catalog = CatalogOptions::new()
+        .lexical_level(LexicalLevel::Level1)
+        .open(&bytes[..])
+        .unwrap();
+    let records = catalog
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(records[3].get_str("CATD", "FILE"), Some("REAåME.TXT"));
+}
+
+#[test]
+fn test_catalog_options_strict_terminators() {
+    use rust_s57::catalog::CatalogOptions;
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    // Overwrite the terminator of the 0001 field in the first record
+    let rcid = bytes
+        .windows(7)
+        .position(|w| w == b"\x1e00001\x1e")
+        .unwrap();
+    bytes[rcid + 6] = b' ';
+    assert!(Catalog::new(&bytes[..]).unwrap().next().unwrap().is_err());
+    let mut catalog = CatalogOptions::new()
+        .strict_terminators(false)
+        .open(&bytes[..])
+        .unwrap();
+    assert_eq!(catalog.next().unwrap().unwrap().id(), Some(1));
+    assert_eq!(catalog.count(), 3);
+}
+
+#[test]
+fn test_catalog_into_reader() {
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let mut catalog = Catalog::new(std::io::Cursor::new(&bytes[..])).unwrap();
+    let first = catalog.next().unwrap().unwrap();
+    let second = catalog.next().unwrap().unwrap();
+    let (offset, length) = second.source_span();
+    assert_eq!(first.source_span().0 + first.source_span().1 as u64, offset);
+
+    let mut cursor = catalog.into_reader();
+    assert_eq!(cursor.position(), offset + length as u64);
+    // The rest of the reader holds the remaining records
+    let mut rest = Vec::new();
+    cursor.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest.len(), bytes.len() - (offset as usize + length));
+    assert_eq!(&rest[..5], b"00103");
+}
+
+#[test]
+fn test_catalog_with_schema() {
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let catalog = Catalog::new(&bytes[..]).unwrap();
+    let schema = catalog.clone_schema();
+
+    // Another file with the same DDR but other records
+    let mut other = bytes.clone();
+    let pos = other.windows(11).position(|w| w == b"CATALOG.031").unwrap();
+    other[pos + 10] = b'2';
+    let expected = Catalog::new(&other[..])
+        .unwrap()
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    let reused = Catalog::with_schema(schema, &other[..]).unwrap();
+    assert_eq!(reused.schema_digest(), catalog.schema_digest());
+    let records = reused
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(records, expected);
+    assert_eq!(records[0].get_str("CATD", "FILE"), Some("CATALOG.032"));
+    assert_eq!(records[0].source_span(), expected[0].source_span());
+
+    let schema = catalog.clone_schema();
+    assert!(Catalog::with_schema(schema, &other[..100]).is_err());
+}
+
+#[test]
+fn test_catalog_with_schema_options() {
+    use rust_s57::catalog::CatalogOptions;
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let catalog = CatalogOptions::new()
+        .retain_raw_fields(true)
+        .open(&bytes[..])
+        .unwrap();
+    let schema = catalog.clone_schema();
+    assert_eq!(catalog.schema().record_length(), schema.record_length());
+    let mut reused =
+        Catalog::with_schema_seekable(schema, std::io::Cursor::new(&bytes[..])).unwrap();
+    let first = reused.next().unwrap().unwrap();
+    assert!(first.field_reader("CATD").is_some());
+    reused.reset().unwrap();
+    assert_eq!(reused.next().unwrap().unwrap(), first);
+}
+
+#[test]
+fn test_catalog_with_schema_longer_ddr() {
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let catalog = Catalog::new(&bytes[..]).unwrap();
+    let expected = Catalog::new(&bytes[..])
+        .unwrap()
+        .map(|r| r.unwrap().get_str("CATD", "FILE").map(String::from))
+        .collect::<Vec<_>>();
+    // The DDR of the other reader is 10 bytes longer than the one of the schema
+    let ddr_length = std::str::from_utf8(&bytes[..5])
+        .unwrap()
+        .parse::<usize>()
+        .unwrap();
+    let mut other = format!("{:05}", ddr_length + 10).into_bytes();
+    other.extend_from_slice(&bytes[5..ddr_length]);
+    other.extend_from_slice(&[b' '; 10]);
+    other.extend_from_slice(&bytes[ddr_length..]);
+
+    let files = |catalog: &mut Catalog<std::io::Cursor<&[u8]>>| {
+        catalog
+            .by_ref()
+            .map(|r| r.unwrap().get_str("CATD", "FILE").map(String::from))
+            .collect::<Vec<_>>()
+    };
+    let schemas = [
+        Catalog::with_schema(catalog.clone_schema(), std::io::Cursor::new(&other[..])),
+        Catalog::with_schema_seekable(catalog.clone_schema(), std::io::Cursor::new(&other[..])),
+    ];
+    for reused in schemas {
+        let mut reused = reused.unwrap();
+        assert_eq!(files(&mut reused), expected);
+        reused.reset().unwrap();
+        assert_eq!(files(&mut reused), expected);
+        reused.build_index().unwrap();
+        let record = reused.seek_to_record(3).unwrap().unwrap();
+        assert_eq!(
+            record.get_str("CATD", "FILE").map(String::from),
+            expected[2]
+        );
+        assert_eq!(reused.count_records().unwrap(), expected.len());
+    }
+}
+
+#[test]
+fn test_catalog_ddr_stats() {
+    use rust_s57::catalog::DdrStats;
+    let catalog = Catalog::new(File::open("tests/CATALOG.031").unwrap()).unwrap();
+    assert_eq!(
+        catalog.ddr_stats(),
+        DdrStats {
+            field_count: 3,
+            field_area_len: 189,
+            directory_len: 49,
+        }
+    );
+}
+
+#[test]
+fn test_catalog_record_id_name() {
+    use rust_s57::catalog::{CatalogOptions, Data};
+    let mut catalog = CatalogOptions::new()
+        .record_id_name("RID")
+        .open(File::open("tests/CATALOG.031").unwrap())
+        .unwrap();
+    let record = catalog.next().unwrap().unwrap();
+    let id_field = record.get("0001").unwrap();
+    assert_eq!(id_field.get("RID"), Some(&Data::Integer(Some(1))));
+    assert_eq!(id_field.get("DRID"), None);
+    assert_eq!(record.id(), Some(1));
+}
+
+#[test]
+fn test_record_field_reader() {
+    use rust_s57::catalog::CatalogOptions;
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let mut catalog = Catalog::new(&bytes[..]).unwrap();
+    assert!(catalog
+        .next()
+        .unwrap()
+        .unwrap()
+        .field_reader("CATD")
+        .is_none());
+    let mut catalog = CatalogOptions::new()
+        .retain_raw_fields(true)
+        .open(&bytes[..])
+        .unwrap();
+    let record = catalog.next().unwrap().unwrap();
+    let mut catd = Vec::new();
+    record
+        .field_reader("CATD")
+        .unwrap()
+        .read_to_end(&mut catd)
+        .unwrap();
+    assert_eq!(&catd[..2], b"CD");
+
+    // The bytes are the field of the record in the file, up to the field terminator
+    let (offset, length) = record.source_span();
+    let source = &bytes[offset as usize..offset as usize + length];
+    let start = source
+        .windows(catd.len())
+        .position(|w| w == &catd[..])
+        .unwrap();
+    assert_eq!(source[start + catd.len()], 0x1e);
+    assert!(record.field_reader("VRID").is_none());
+}
+
+#[test]
+fn test_catalog_peek() {
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let mut catalog = Catalog::new(std::io::Cursor::new(&bytes[..])).unwrap();
+    let first = catalog.peek().unwrap().unwrap().clone();
+    assert_eq!(catalog.peek().unwrap().unwrap(), &first);
+    assert_eq!(catalog.estimated_remaining().unwrap(), None);
+    assert_eq!(catalog.next().unwrap().unwrap(), first);
+
+    let second = catalog.peek().unwrap().unwrap().clone();
+    assert_ne!(second, first);
+    assert_eq!(catalog.next().unwrap().unwrap(), second);
+    assert_eq!(catalog.by_ref().count(), 2);
+    assert!(catalog.peek().is_none());
+    assert!(catalog.next().is_none());
+}
+
+// The catalog with the base address of the field area of the first Data Record corrupted
+fn corrupt_first_record() -> Vec<u8> {
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let ddr_length = std::str::from_utf8(&bytes[..5])
+        .unwrap()
+        .parse::<usize>()
+        .unwrap();
+    bytes[ddr_length + 12..ddr_length + 17].copy_from_slice(b"x0000");
+    bytes
+}
+
+#[test]
+fn test_catalog_peek_error() {
+    let bytes = corrupt_first_record();
+    let mut catalog = Catalog::new(&bytes[..]).unwrap();
+    assert!(catalog.next().unwrap().is_err());
+    let expected = catalog.map(|r| r.is_ok()).collect::<Vec<bool>>();
+
+    // The rewind is exact without the absolute position of the first Data Record
+    let mut catalog = Catalog::new(std::io::Cursor::new(&bytes[..])).unwrap();
+    assert!(catalog.peek().unwrap().is_err());
+    assert!(catalog.peek().unwrap().is_err());
+    assert!(catalog.next().unwrap().is_err());
+    assert_eq!(catalog.map(|r| r.is_ok()).collect::<Vec<bool>>(), expected);
+
+    let mut catalog = Catalog::new(&bytes[..]).unwrap();
+    assert!(catalog.peek_buffered().unwrap().is_err());
+    assert!(catalog.peek_buffered().unwrap().is_err());
+    assert!(catalog.next().unwrap().is_err());
+    assert_eq!(catalog.map(|r| r.is_ok()).collect::<Vec<bool>>(), expected);
+}
+
+#[test]
+fn test_catalog_peek_buffered() {
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    let expected = Catalog::new(&bytes[..])
+        .unwrap()
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    let mut catalog = Catalog::new(&bytes[..]).unwrap();
+    for record in &expected {
+        assert_eq!(catalog.peek_buffered().unwrap().unwrap(), record);
+        assert_eq!(catalog.peek_buffered().unwrap().unwrap(), record);
+        assert_eq!(&catalog.next().unwrap().unwrap(), record);
+    }
+    assert!(catalog.peek_buffered().is_none());
+    assert!(catalog.next().is_none());
+}
+
+#[test]
+fn test_open_exchange_set() {
+    use rust_s57::catalog::FileImplementation;
+    // The cell AA5OTH1.000 is in the volume subfolder AA5
+    let set = Catalog::open_exchange_set("tests/ENC_ROOT").unwrap();
+    let root = std::fs::canonicalize("tests/ENC_ROOT").unwrap();
+    assert_eq!(set.root(), root);
+    assert_eq!(set.files().len(), 4);
+    assert_eq!(set.files()[0].path, root.join("CATALOG.031"));
+    assert_eq!(
+        set.files()[0].implementation,
+        Some(FileImplementation::Ascii)
+    );
+
+    let cells = set.cells().collect::<Vec<_>>();
+    assert_eq!(cells.len(), 1);
+    assert_eq!(cells[0].path, root.join("AA5").join("OTH1.000"));
+    assert!(cells[0].path.is_absolute() && cells[0].path.is_file());
+    assert_eq!(
+        cells[0].bbox,
+        Some((10.0, 10.1666667, 10.1666667, 10.3333334))
+    );
+    assert_eq!(cells[0].record.get_str("CATD", "CRCS"), Some("717BD6F2"));
+
+    assert!(Catalog::open_exchange_set("tests/NO_SUCH_ROOT").is_err());
+}
+
+#[test]
+fn test_catalog_strict_field_lengths() {
+    use rust_s57::catalog::CatalogOptions;
+    let mut bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    // A unit separator splits SLAT of the second record, so COMT ends before the last one
+    let pos = bytes
+        .windows(11)
+        .position(|w| w == b"10.0000000\x1f")
+        .unwrap();
+    bytes[pos + 2] = 0x1f;
+    let record = Catalog::new(&bytes[..]).unwrap().nth(1).unwrap().unwrap();
+    assert_eq!(record.get_f64("CATD", "SLAT"), Some(10.0));
+    assert_eq!(record.get_str("CATD", "COMT"), Some("717BD6F2"));
+
+    let mut catalog = CatalogOptions::new()
+        .strict_field_lengths(true)
+        .open(&bytes[..])
+        .unwrap();
+    assert!(catalog.next().unwrap().is_ok());
+    let err = catalog.next().unwrap().unwrap_err();
+    match err.kind() {
+        ErrorKind::FieldLengthMismatch {
+            field,
+            expected,
+            consumed,
+        } => {
+            assert_eq!(field, "CATD");
+            assert_eq!(*expected, 91);
+            assert_eq!(*consumed, 90);
+        }
+        kind => panic!("unexpected error {:?}", kind),
+    }
+
+    // The records of an intact catalog take up their fields exactly
+    let catalog = CatalogOptions::new()
+        .strict_field_lengths(true)
+        .open(File::open("tests/CATALOG.031").unwrap())
+        .unwrap();
+    assert_eq!(catalog.filter(|record| record.is_ok()).count(), 4);
+}
@@ -0,0 +1,132 @@
+#![cfg(feature = "std")]
+use rust_s57::catalog::Data;
+use rust_s57::cell::Cell;
+use std::fs::File;
+
+#[test]
+fn test_parse_cell() {
+    let cf = File::open("tests/CELL.000").unwrap();
+    let mut cell = Cell::new(cf).unwrap();
+    let records = cell
+        .records()
+        .collect::<rust_s57::catalog::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(records.len(), 4);
+
+    let dsid = records[0].get("DSID").unwrap();
+    assert_eq!(records[0].id(), Some(1));
+    assert_eq!(dsid.get("RCNM"), Some(&Data::Integer(Some(10))));
+    assert_eq!(
+        dsid.get("DSNM"),
+        Some(&Data::String("CELL.000".to_string()))
+    );
+
+    let frid = records[2].get("FRID").unwrap();
+    assert_eq!(records[2].id(), Some(3));
+    assert_eq!(frid.get("RCID"), Some(&Data::Integer(Some(2))));
+    assert_eq!(frid.get("OBJL"), Some(&Data::Integer(Some(42))));
+}
+
+#[test]
+fn test_cell_iter_fields_in_directory_order() {
+    let cf = File::open("tests/CELL.000").unwrap();
+    let mut cell = Cell::new(cf).unwrap();
+    let record = cell.records().nth(1).unwrap().unwrap();
+    let tags = record
+        .iter_fields()
+        .map(|(tag, _)| tag)
+        .collect::<Vec<&str>>();
+    assert_eq!(tags, vec!["0001", "FRID"]);
+}
+
+#[test]
+fn test_cell_field_tags() {
+    let cf = File::open("tests/CELL.000").unwrap();
+    let mut cell = Cell::new(cf).unwrap();
+    let record = cell.records().nth(3).unwrap().unwrap();
+    assert_eq!(
+        record.field_tags().collect::<Vec<&str>>(),
+        vec!["0001", "VRID", "SG2D"]
+    );
+}
+
+#[test]
+fn test_cell_repeating_field() {
+    let cf = File::open("tests/CELL.000").unwrap();
+    let mut cell = Cell::new(cf).unwrap();
+    let record = cell.records().nth(3).unwrap().unwrap();
+    let sg2d = record.get_array("SG2D").unwrap();
+    assert_eq!(sg2d.len(), 2);
+    assert_eq!(sg2d[0].get("YCOO"), Some(&Data::Integer(Some(593456789))));
+    assert_eq!(sg2d[0].get("XCOO"), Some(&Data::Integer(Some(-102500000))));
+    assert_eq!(sg2d[1].get("YCOO"), Some(&Data::Integer(Some(593500000))));
+    assert_eq!(sg2d[1].get("XCOO"), Some(&Data::Integer(Some(-102400000))));
+    assert_eq!(record.coordinate(10_000_000), Some((59.3456789, -10.25)));
+    assert!(record.get_array("VRID").is_none());
+}
+
+#[test]
+fn test_cell_record_name() {
+    use rust_s57::catalog::RecordName;
+    let cf = File::open("tests/CELL.000").unwrap();
+    let mut cell = Cell::new(cf).unwrap();
+    let names = cell
+        .records()
+        .map(|r| r.unwrap().record_name())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            Some(RecordName::DS),
+            Some(RecordName::FE),
+            Some(RecordName::FE),
+            Some(RecordName::VI)
+        ]
+    );
+}
+
+#[test]
+fn test_cell_record_header() {
+    use rust_s57::catalog::{RecordHeader, RecordName};
+    let cf = File::open("tests/CELL.000").unwrap();
+    let mut cell = Cell::new(cf).unwrap();
+    let record = cell.records().nth(2).unwrap().unwrap();
+    assert_eq!(
+        record.header(),
+        Some(RecordHeader {
+            id: 3,
+            record_name: Some(RecordName::FE),
+            record_id: Some(2),
+        })
+    );
+    assert_eq!(record.id(), Some(3));
+}
+
+#[test]
+fn test_cell_parse_coordinates() {
+    use rust_s57::cell::parse_coordinates;
+    let cf = File::open("tests/CELL.000").unwrap();
+    let mut cell = Cell::new(cf).unwrap();
+    let record = cell.records().nth(3).unwrap().unwrap();
+    let coordinates = parse_coordinates(record.get("SG2D").unwrap(), 10_000_000, 10).unwrap();
+    assert_eq!(
+        coordinates,
+        vec![[-10.25, 59.3456789, 0.0], [-10.24, 59.35, 0.0]]
+    );
+}
+
+#[test]
+fn test_cell_source_span() {
+    let bytes = std::fs::read("tests/CELL.000").unwrap();
+    let mut cell = Cell::new(&bytes[..]).unwrap();
+    let spans = cell
+        .records()
+        .map(|r| r.unwrap().source_span())
+        .collect::<Vec<_>>();
+    assert_eq!(spans.len(), 4);
+    for pair in spans.windows(2) {
+        assert_eq!(pair[1].0, pair[0].0 + pair[0].1 as u64);
+    }
+    let (offset, length) = spans[3];
+    assert_eq!(offset as usize + length, bytes.len());
+}
@@ -0,0 +1,16 @@
+#![cfg(feature = "csv")]
+use rust_s57::catalog::Catalog;
+use rust_s57::to_csv::catalog_to_csv;
+
+#[test]
+fn test_catalog_to_csv() {
+    let bytes = std::fs::read("tests/CATALOG.031").unwrap();
+    // The DDR and the first two records, which are 101 and 150 bytes long
+    let catalog = Catalog::new(&bytes[..262 + 101 + 150]).unwrap();
+    let mut out = Vec::new();
+    catalog_to_csv(catalog, &mut out).unwrap();
+    let expected = "RCNM,RCID,FILE,LFIL,VOLM,IMPL,SLAT,WLON,NLAT,ELON,CRCS,COMT\r\n\
+                    CD,1,CATALOG.031,,V01X01,ASC,,,,,,\r\n\
+                    CD,2,AA5OTHER.000,,V01X01,BIN,10,10.1666667,10.1666667,10.3333334,717BD6F2,\r\n";
+    assert_eq!(String::from_utf8(out).unwrap(), expected);
+}
@@ -0,0 +1,13 @@
+#![cfg(feature = "geojson")]
+use rust_s57::catalog::Catalog;
+use rust_s57::to_geojson::catalog_to_geojson;
+use std::fs::File;
+
+#[test]
+fn test_catalog_to_geojson() {
+    let cf = File::open("tests/BBOX.031").unwrap();
+    let catalog = Catalog::new(cf).unwrap();
+    let actual = catalog_to_geojson(catalog).unwrap();
+    let expected = std::fs::read_to_string("tests/BBOX.geojson").unwrap();
+    assert_eq!(actual, expected);
+}
@@ -0,0 +1,59 @@
+// Uses only the slice parsers, so these also run with `--no-default-features`
+use rust_s57::iso8211::{
+    parse_directory, parse_field_controls, parse_format_controls, parse_leader, DirectoryEntry,
+    EntryMap, ParseError,
+};
+
+const CATALOG: &[u8] = include_bytes!("CATALOG.031");
+
+#[test]
+fn test_parse_leader_from_slice() {
+    let leader = parse_leader(&CATALOG[..24]).unwrap();
+    assert_eq!(leader.record_length, 262);
+    assert_eq!(leader.interchange_level, b'3');
+    assert_eq!(leader.leader_identifier, b'L');
+    assert_eq!(leader.field_control_length, *b"09");
+    assert_eq!(leader.base_address, 73);
+    assert_eq!(
+        leader.entry_map,
+        EntryMap {
+            length_size: 6,
+            position_size: 6,
+            tag_size: 4,
+        }
+    );
+    assert_eq!(parse_leader(&CATALOG[..23]), Err(ParseError::TooShort));
+}
+
+#[test]
+fn test_parse_directory_into_buffer() {
+    let leader = parse_leader(&CATALOG[..24]).unwrap();
+    let mut entries = [DirectoryEntry::default(); 3];
+    let directory = &CATALOG[24..leader.base_address - 1];
+    let count = parse_directory(directory, &leader.entry_map, &mut entries).unwrap();
+    assert_eq!(count, 3);
+    assert_eq!(entries[0].tag, b"0000");
+    assert_eq!(entries[2].tag, b"CATD");
+    assert_eq!((entries[2].length, entries[2].position), (122, 67));
+    assert_eq!(
+        parse_directory(directory, &leader.entry_map, &mut entries[..2]),
+        Err(ParseError::BufferTooSmall)
+    );
+
+    let fields = &CATALOG[leader.base_address..];
+    let catd = &fields[entries[2].position..entries[2].position + entries[2].length];
+    let controls = parse_field_controls(&catd[..9]).unwrap();
+    assert_eq!(controls.data_structure, b'1');
+    assert_eq!(controls.escape_sequence, b"   ");
+}
+
+#[test]
+fn test_parse_format_controls_into_buffer() {
+    let mut controls = [&[][..]; 4];
+    let count = parse_format_controls(b"(A(2),I(10),2(R(4)))", &mut controls).unwrap();
+    assert_eq!(&controls[..count], &[&b"A(2)"[..], b"I(10)", b"2(R(4))"]);
+    assert_eq!(
+        parse_format_controls(b"(A(2),I(10)", &mut controls),
+        Err(ParseError::InvalidFormatControls)
+    );
+}
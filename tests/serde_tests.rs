@@ -0,0 +1,29 @@
+#![cfg(feature = "serde")]
+use rust_s57::catalog::Catalog;
+use serde_json::json;
+use std::fs::File;
+
+#[test]
+fn test_record_to_json() {
+    let cf = File::open("tests/CATALOG.031").unwrap();
+    let record = Catalog::new(cf).unwrap().nth(1).unwrap().unwrap();
+    let actual = serde_json::to_value(&record).unwrap();
+    let expected = json!({
+        "0001": { "DRID": 2 },
+        "CATD": {
+            "RCNM": "CD",
+            "RCID": 2,
+            "FILE": "AA5OTHER.000",
+            "LFIL": "",
+            "VOLM": "V01X01",
+            "IMPL": "BIN",
+            "SLAT": 10.0,
+            "WLON": 10.1666667,
+            "NLAT": 10.1666667,
+            "ELON": 10.3333334,
+            "CRCS": "717BD6F2",
+            "COMT": "",
+        }
+    });
+    assert_eq!(actual, expected);
+}
@@ -0,0 +1,113 @@
+#![cfg(feature = "std")]
+use rust_s57::catalog::{Data, Record, Result};
+use rust_s57::cell::Cell;
+use rust_s57::error::ErrorKind;
+use rust_s57::update::apply_update;
+use std::fs::File;
+
+fn open(path: &str) -> Cell<File> {
+    Cell::new(File::open(path).unwrap()).unwrap()
+}
+
+fn base() -> Vec<Record> {
+    open("tests/UPDATE.000")
+        .records()
+        .collect::<Result<Vec<_>>>()
+        .unwrap()
+}
+
+fn find<'a>(records: &'a [Record], tag: &str, rcid: i64) -> Option<&'a Record> {
+    records
+        .iter()
+        .find(|r| r.get_i64(tag, "RCID") == Some(rcid))
+}
+
+#[test]
+fn test_apply_update_insert_delete_modify() {
+    let mut records = base();
+    let warnings = apply_update(&mut records, open("tests/UPDATE.001")).unwrap();
+    assert!(warnings.is_empty());
+    assert_eq!(records.len(), 4);
+
+    // Inserted
+    let inserted = find(&records, "FRID", 3).unwrap();
+    assert_eq!(inserted.get_i64("FRID", "OBJL"), Some(7));
+    assert_eq!(inserted.get_i64("FRID", "RUIN"), Some(1));
+
+    // Deleted
+    assert!(find(&records, "FRID", 2).is_none());
+    assert!(find(&records, "FRID", 1).is_some());
+
+    // Modified
+    let modified = find(&records, "VRID", 1).unwrap();
+    assert_eq!(modified.get_i64("VRID", "RVER"), Some(2));
+    assert_eq!(modified.get_i64("VRID", "RUIN"), Some(1));
+    let sg2d = modified.get_array("SG2D").unwrap();
+    assert_eq!(sg2d.len(), 1);
+    assert_eq!(sg2d[0].get("YCOO"), Some(&Data::Integer(Some(593500000))));
+
+    assert_eq!(records[0].get_str("DSID", "UPDN"), Some("1"));
+    assert_eq!(records[0].get_str("DSID", "DSNM"), Some("UPDATE.000"));
+}
+
+#[test]
+fn test_apply_update_padding_warning() {
+    use rust_s57::catalog::Warning;
+    let mut bytes = std::fs::read("tests/UPDATE.001").unwrap();
+    let length = bytes.len() as u64;
+    bytes.extend_from_slice(&[0; 8]);
+    let mut records = base();
+    let warnings = apply_update(&mut records, Cell::new(&bytes[..]).unwrap()).unwrap();
+    assert_eq!(
+        warnings,
+        vec![Warning::UnexpectedPadding {
+            offset: length,
+            length: 8
+        }]
+    );
+}
+
+#[test]
+fn test_apply_update_sequence() {
+    let mut records = base();
+    apply_update(&mut records, open("tests/UPDATE.001")).unwrap();
+    apply_update(&mut records, open("tests/UPDATE.002")).unwrap();
+    assert_eq!(records[0].get_str("DSID", "UPDN"), Some("2"));
+    let modified = find(&records, "FRID", 3).unwrap();
+    assert_eq!(modified.get_i64("FRID", "OBJL"), Some(8));
+    assert_eq!(modified.get_i64("FRID", "RVER"), Some(2));
+}
+
+#[test]
+fn test_apply_update_out_of_order() {
+    let mut records = base();
+    let err = apply_update(&mut records, open("tests/UPDATE.002")).unwrap_err();
+    match err.kind() {
+        ErrorKind::UpdateOutOfOrder { expected, found } => {
+            assert_eq!(expected, "1");
+            assert_eq!(found, "2");
+        }
+        kind => panic!("unexpected error {}", kind),
+    }
+    assert_eq!(records.len(), 4);
+    assert_eq!(records[0].get_str("DSID", "UPDN"), Some("0"));
+
+    apply_update(&mut records, open("tests/UPDATE.001")).unwrap();
+    let err = apply_update(&mut records, open("tests/UPDATE.001")).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::UpdateOutOfOrder { .. }));
+}
+
+#[test]
+fn test_apply_update_delete_missing_record() {
+    let mut records = base();
+    records.retain(|r| r.get_i64("FRID", "RCID") != Some(2));
+    let err = apply_update(&mut records, open("tests/UPDATE.001")).unwrap_err();
+    match err.kind() {
+        ErrorKind::UpdateTargetMissing(rcnm, rcid) => assert_eq!((*rcnm, *rcid), (100, 2)),
+        kind => panic!("unexpected error {}", kind),
+    }
+    // Nothing is applied when an update is rejected
+    assert_eq!(records.len(), 3);
+    assert!(find(&records, "FRID", 3).is_none());
+    assert_eq!(records[0].get_str("DSID", "UPDN"), Some("0"));
+}
@@ -0,0 +1,32 @@
+#![cfg(feature = "wkt")]
+use rust_s57::to_wkt::{feature_to_wkt, Geometry};
+
+#[test]
+fn test_point_to_wkt() {
+    let point = Geometry::Point([-10.25, 59.3456789]);
+    assert_eq!(feature_to_wkt(&point), "POINT (-10.25 59.3456789)");
+}
+
+#[test]
+fn test_line_to_wkt() {
+    let line = Geometry::Line(vec![[10.0, 59.0], [10.5, 59.25], [11.0, 59.5]]);
+    assert_eq!(
+        feature_to_wkt(&line),
+        "LINESTRING (10 59, 10.5 59.25, 11 59.5)"
+    );
+    assert_eq!(feature_to_wkt(&Geometry::Line(vec![])), "LINESTRING EMPTY");
+}
+
+#[test]
+fn test_polygon_to_wkt() {
+    // The open exterior ring is closed, the closed hole is left as it is
+    let polygon = Geometry::Polygon(vec![
+        vec![[0.0, 0.0], [4.0, 0.0], [4.0, 4.0], [0.0, 4.0]],
+        vec![[1.0, 1.0], [2.0, 1.0], [2.0, 2.0], [1.0, 1.0]],
+    ]);
+    assert_eq!(
+        feature_to_wkt(&polygon),
+        "POLYGON ((0 0, 4 0, 4 4, 0 4, 0 0), (1 1, 2 1, 2 2, 1 1))"
+    );
+    assert_eq!(feature_to_wkt(&Geometry::Polygon(vec![])), "POLYGON EMPTY");
+}